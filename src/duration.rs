@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+use anyhow::anyhow;
+
+/// Parse a human duration such as `90s`, `10m`, `48hrs`, `3d` or `1w`.
+/// A bare number is taken as seconds.
+pub fn parse_duration(s: &str) -> Result<Duration, anyhow::Error> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (amount, unit) = s.split_at(split);
+
+    let amount: u64 = amount.parse().map_err(|_| {
+        anyhow!(
+            "'{}' is not a duration, expected something like 30m, 48hrs, 3d or 1w",
+            s
+        )
+    })?;
+
+    let seconds_per_unit = match unit.trim().to_lowercase().as_str() {
+        "" | "s" | "sec" | "secs" | "second" | "seconds" => 1,
+        "m" | "min" | "mins" | "minute" | "minutes" => 60,
+        "h" | "hr" | "hrs" | "hour" | "hours" => 60 * 60,
+        "d" | "day" | "days" => 24 * 60 * 60,
+        "w" | "wk" | "wks" | "week" | "weeks" => 7 * 24 * 60 * 60,
+        other => {
+            return Err(anyhow!(
+                "unknown duration unit '{}' in '{}', use s, m, h/hrs, d or w",
+                other,
+                s
+            ))
+        }
+    };
+
+    amount
+        .checked_mul(seconds_per_unit)
+        .map(Duration::from_secs)
+        .ok_or_else(|| anyhow!("'{}' is too long a duration", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_bare_number_is_seconds() {
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+    }
+
+    #[test]
+    fn units() {
+        assert_eq!(parse_duration("10m").unwrap(), Duration::from_secs(600));
+        assert_eq!(
+            parse_duration("48hrs").unwrap(),
+            Duration::from_secs(48 * 3600)
+        );
+        assert_eq!(
+            parse_duration(" 3 days ").unwrap(),
+            Duration::from_secs(3 * 86400)
+        );
+        assert_eq!(
+            parse_duration("1W").unwrap(),
+            Duration::from_secs(7 * 86400)
+        );
+    }
+
+    #[test]
+    fn not_a_duration() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("-1h").is_err());
+        assert!(parse_duration("3 fortnights").is_err());
+    }
+
+    #[test]
+    fn too_long_is_an_error_not_an_overflow() {
+        assert!(parse_duration("40000000000000w").is_err());
+        assert!(parse_duration("99999999999999999999").is_err());
+    }
+}
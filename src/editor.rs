@@ -0,0 +1,138 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{anyhow, Context, Result};
+use log::debug;
+
+/// Everything from this line on is left out of what was written
+const SCISSORS: &str = "# ------------------------ >8 ------------------------";
+
+/// `$VISUAL`, then `$EDITOR`, then the platform's default
+fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| match cfg!(windows) {
+            true => "notepad".to_string(),
+            false => "vi".to_string(),
+        })
+}
+
+/// The editor can have arguments, e.g. `code --wait`
+fn run_editor(file: &Path) -> Result<()> {
+    let editor = editor_command();
+    debug!("editing {} with `{}`", file.display(), editor);
+
+    let status = if cfg!(windows) {
+        Command::new("cmd")
+            .args(["/C", &format!("{} \"{}\"", editor, file.display())])
+            .status()
+    } else {
+        Command::new("sh")
+            .args(["-c", &format!("{} \"$1\"", editor), "sh"])
+            .arg(file)
+            .status()
+    }
+    .with_context(|| format!("`{}` could not be run", editor))?;
+
+    match status.success() {
+        true => Ok(()),
+        false => Err(anyhow!(
+            "`{}` exited with {}, nothing was changed",
+            editor,
+            status
+        )),
+    }
+}
+
+/// Open the text in the editor, returning what was saved (without the help below the
+/// scissors line). The file is Markdown, so the editor highlights it
+pub fn edit_text(name: &str, text: &str, help: &str) -> Result<String> {
+    // an issue id, G/42, is not a file name
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect();
+    let mut file = tempfile::Builder::new()
+        .prefix(&format!("tskmstr-{}-", name))
+        .suffix(".md")
+        .tempfile()
+        .context("Failed to create a file to edit")?;
+
+    let contents = format!(
+        "{}\n\n{}\n# Everything below the line above is ignored.\n{}\n",
+        text.trim_end(),
+        SCISSORS,
+        help.lines()
+            .map(|line| format!("# {}", line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+    let path = file.path().to_path_buf();
+    file.write_all(contents.as_bytes())
+        .and_then(|_| file.flush())
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    // the editor can replace the file, so it is read back by its name
+    let edited = run_editor(&path).and_then(|_| {
+        std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))
+    });
+    if let Err(e) = file.close() {
+        debug!("unable to remove {}: {}", path.display(), e);
+    }
+
+    let edited = edited?;
+    let kept = match edited.find(SCISSORS) {
+        Some(at) => &edited[..at],
+        None => &edited,
+    };
+    Ok(kept.replace("\r\n", "\n").trim().to_string())
+}
+
+/// The title on the first line, the body after it
+pub fn title_and_body(title: &str, body: &str) -> String {
+    match body.trim() {
+        "" => format!("{}\n\n", title),
+        body => format!("{}\n\n{}", title, body),
+    }
+}
+
+/// Read back what `title_and_body` wrote, after editing
+pub fn split_title_and_body(text: &str) -> (String, String) {
+    let (title, body) = text.split_once('\n').unwrap_or((text, ""));
+    (title.trim().to_string(), body.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_first_line_is_the_title() {
+        assert_eq!(
+            split_title_and_body("  A title \n\nThe body\n\nmore of it\n"),
+            ("A title".to_string(), "The body\n\nmore of it".to_string())
+        );
+    }
+
+    #[test]
+    fn a_title_alone() {
+        assert_eq!(
+            split_title_and_body("A title"),
+            ("A title".to_string(), String::new())
+        );
+        assert_eq!(split_title_and_body(""), (String::new(), String::new()));
+    }
+
+    #[test]
+    fn reads_back_what_title_and_body_wrote() {
+        for (title, body) in [("A title", "The body\nof it"), ("A title", "")] {
+            assert_eq!(
+                split_title_and_body(&title_and_body(title, body)),
+                (title.to_string(), body.to_string())
+            );
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use std::fmt;
+
+use reqwest::{Response, StatusCode};
+
+/// The failures tskmstr reports to the user, rather than panicking on.
+/// Each has its own exit code, so scripts can tell them apart.
+#[derive(Debug)]
+pub enum TskmstrError {
+    /// no issue store has this `id:` in the config
+    UnknownStore { id: String, known: Vec<String> },
+
+    /// no provider has this `provider_id:` in the config
+    UnknownProvider { id: String, known: Vec<String> },
+
+    /// not a `<issue store id>/<issue id>` reference
+    MalformedId { id: String, known: Vec<String> },
+
+    /// nothing is marked `defaults.for_new_tasks: true`
+    NoDefaultStore,
+
+    /// no issue store is the repository of a remote of the current git repository
+    NoStoreHere { remotes: Vec<String> },
+
+    /// the API token for a provider could not be found
+    MissingCredential { provider_id: String, reason: String },
+
+    /// `config check` found problems in the config file
+    InvalidConfig { problems: usize },
+
+    /// the provider answered, but not with a success
+    Http {
+        status: StatusCode,
+        url: String,
+        body: String,
+    },
+}
+
+impl TskmstrError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            TskmstrError::UnknownStore { .. } | TskmstrError::UnknownProvider { .. } => 3,
+            TskmstrError::MalformedId { .. } => 4,
+            TskmstrError::NoDefaultStore | TskmstrError::NoStoreHere { .. } => 5,
+            TskmstrError::MissingCredential { .. } => 6,
+            TskmstrError::Http { .. } => 7,
+            TskmstrError::InvalidConfig { .. } => 8,
+        }
+    }
+}
+
+impl TskmstrError {
+    /// Read the status, URL and body of a failed response
+    pub async fn from_response(response: Response) -> TskmstrError {
+        let status = response.status();
+        let url = response.url().to_string();
+        let body = response.text().await.unwrap_or_default();
+        TskmstrError::Http { status, url, body }
+    }
+}
+
+/// A failed response, along with what we were trying to do
+pub async fn http_error(response: Response, doing: String) -> anyhow::Error {
+    anyhow::Error::new(TskmstrError::from_response(response).await).context(doing)
+}
+
+/// any other error (a bad config file, a network failure) exits with 1
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    err.downcast_ref::<TskmstrError>()
+        .map_or(1, TskmstrError::exit_code)
+}
+
+fn describe_known(known: &[String]) -> String {
+    // the same id can be used by more than one provider
+    let mut unique: Vec<&str> = Vec::new();
+    for id in known {
+        if !unique.contains(&id.as_str()) {
+            unique.push(id);
+        }
+    }
+
+    if unique.is_empty() {
+        "there are no issue stores in the config".to_string()
+    } else {
+        format!("valid issue store ids are: {}", unique.join(", "))
+    }
+}
+
+impl fmt::Display for TskmstrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TskmstrError::UnknownStore { id, known } => {
+                write!(f, "there is no issue store '{}', {}", id, describe_known(known))?;
+                // a typo of the case is the usual mistake
+                if let Some(close) = known.iter().find(|k| k.eq_ignore_ascii_case(id)) {
+                    write!(f, " (did you mean '{}'?)", close)?;
+                }
+                Ok(())
+            }
+            TskmstrError::UnknownProvider { id, known } => write!(
+                f,
+                "there is no provider '{}', valid provider ids are: {}",
+                id,
+                known.join(", ")
+            ),
+            TskmstrError::MalformedId { id, known } => write!(
+                f,
+                "'{}' is not an issue id, expected <issue store id>/<issue id> (e.g. {}/42), {}",
+                id,
+                known.first().map_or("T", |k| k.as_str()),
+                describe_known(known)
+            ),
+            TskmstrError::NoDefaultStore => write!(
+                f,
+                "no default issue store, set `default_store:` or `defaults: {{ for_new_tasks: true }}` on one in the config, or pass --issue-store-id"
+            ),
+            TskmstrError::NoStoreHere { remotes } if remotes.is_empty() => write!(
+                f,
+                "the current directory is not in a git repository with a remote"
+            ),
+            TskmstrError::NoStoreHere { remotes } => write!(
+                f,
+                "no issue store is the repository of the git remotes {}",
+                remotes.join(", ")
+            ),
+            TskmstrError::MissingCredential {
+                provider_id,
+                reason,
+            } => write!(f, "no API token for {}: {}", provider_id, reason),
+            TskmstrError::InvalidConfig { problems } => {
+                write!(f, "the config has {} problem(s)", problems)
+            }
+            TskmstrError::Http { status, url, body } => {
+                write!(f, "{} returned {}", url, status)?;
+                if !body.trim().is_empty() {
+                    write!(f, ": {}", body.trim())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for TskmstrError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(err: TskmstrError) -> i32 {
+        exit_code(&anyhow::Error::new(err))
+    }
+
+    fn known() -> Vec<String> {
+        vec!["G".to_string()]
+    }
+
+    #[test]
+    fn each_failure_has_its_exit_code() {
+        let unknown_store = TskmstrError::UnknownStore {
+            id: "X".to_string(),
+            known: known(),
+        };
+        assert_eq!(code(unknown_store), 3);
+        let unknown_provider = TskmstrError::UnknownProvider {
+            id: "gl".to_string(),
+            known: vec!["gh".to_string()],
+        };
+        assert_eq!(code(unknown_provider), 3);
+        let malformed_id = TskmstrError::MalformedId {
+            id: "42".to_string(),
+            known: known(),
+        };
+        assert_eq!(code(malformed_id), 4);
+        assert_eq!(code(TskmstrError::NoDefaultStore), 5);
+        let no_store_here = TskmstrError::NoStoreHere {
+            remotes: vec!["git@github.com:o/r.git".to_string()],
+        };
+        assert_eq!(code(no_store_here), 5);
+        let missing_credential = TskmstrError::MissingCredential {
+            provider_id: "gh".to_string(),
+            reason: "not set".to_string(),
+        };
+        assert_eq!(code(missing_credential), 6);
+        let http = TskmstrError::Http {
+            status: StatusCode::NOT_FOUND,
+            url: "https://api.github.com/repos/o/r/issues/42".to_string(),
+            body: String::new(),
+        };
+        assert_eq!(code(http), 7);
+        assert_eq!(code(TskmstrError::InvalidConfig { problems: 2 }), 8);
+    }
+
+    #[test]
+    fn the_exit_code_survives_context() {
+        let err = anyhow::Error::new(TskmstrError::NoDefaultStore).context("Unable to add a task");
+        assert_eq!(exit_code(&err), 5);
+    }
+
+    #[test]
+    fn other_errors_exit_with_one() {
+        assert_eq!(exit_code(&anyhow::anyhow!("a network failure")), 1);
+    }
+}
@@ -0,0 +1,154 @@
+use std::process::Command;
+
+use log::debug;
+use reqwest::Url;
+
+/// A remote of the git repository the current directory is in,
+/// e.g. `git@github.com:owner/repo.git` is host `github.com`, path `owner/repo`
+#[derive(Debug, Clone, PartialEq)]
+pub struct GitRemote {
+    pub name: String,
+    pub host: String,
+    pub path: String,
+}
+
+impl GitRemote {
+    /// Read the https, ssh and scp-like (`git@host:path`) forms of a remote URL
+    pub fn parse(name: &str, url: &str) -> Option<GitRemote> {
+        let (host, path) = match Url::parse(url) {
+            Ok(parsed) if parsed.has_host() => {
+                (parsed.host_str()?.to_string(), parsed.path().to_string())
+            }
+            // git@github.com:owner/repo.git
+            _ => {
+                let (user_host, path) = url.split_once(':')?;
+                let host = user_host.rsplit('@').next()?;
+                (host.to_string(), path.to_string())
+            }
+        };
+
+        let path = path.trim_matches('/');
+        let path = path.strip_suffix(".git").unwrap_or(path);
+        if host.is_empty() || !path.contains('/') {
+            return None;
+        }
+
+        Some(GitRemote {
+            name: name.to_string(),
+            host: host.to_lowercase(),
+            path: path.to_string(),
+        })
+    }
+
+    /// Whether the remote is on the server of this API endpoint; the GitHub API is on
+    /// `api.github.com`, GitHub Enterprise and GitLab are on the server itself
+    pub fn is_on(&self, endpoint: &str) -> bool {
+        Url::parse(endpoint)
+            .ok()
+            .and_then(|url| url.host_str().map(|h| h.to_lowercase()))
+            .is_some_and(|host| host.strip_prefix("api.").unwrap_or(&host) == self.host)
+    }
+
+    /// The same repository, ignoring case as GitHub and GitLab do
+    pub fn is_path(&self, path: &str) -> bool {
+        self.path.eq_ignore_ascii_case(path.trim_matches('/'))
+    }
+}
+
+impl std::fmt::Display for GitRemote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}/{})", self.name, self.host, self.path)
+    }
+}
+
+/// The remotes of the git repository of the current directory, `origin` first, with their
+/// URLs as git fetches them (after `insteadOf`).
+/// None when git isn't installed, or this isn't a git repository
+pub fn current_remotes() -> Vec<GitRemote> {
+    let output = match Command::new("git").args(["remote", "-v"]).output() {
+        Ok(output) if output.status.success() => output,
+        Ok(_) => return Vec::new(),
+        Err(e) => {
+            debug!("git could not be run: {}", e);
+            return Vec::new();
+        }
+    };
+
+    // origin\tgit@github.com:owner/repo.git (fetch), and the same again for (push)
+    let mut remotes: Vec<GitRemote> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (name, url) = line.split_once('\t')?;
+            let url = url.strip_suffix(" (fetch)")?;
+            GitRemote::parse(name, url.trim())
+        })
+        .collect();
+    remotes.sort_by_key(|remote| remote.name != "origin");
+
+    debug!("git remotes: {:?}", remotes);
+    remotes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remote(host: &str, path: &str) -> Option<GitRemote> {
+        Some(GitRemote {
+            name: "origin".to_string(),
+            host: host.to_string(),
+            path: path.to_string(),
+        })
+    }
+
+    #[test]
+    fn https() {
+        assert_eq!(
+            GitRemote::parse("origin", "https://github.com/owner/repo.git"),
+            remote("github.com", "owner/repo")
+        );
+        assert_eq!(
+            GitRemote::parse(
+                "origin",
+                "https://user@GitLab.example.com/group/sub/project/"
+            ),
+            remote("gitlab.example.com", "group/sub/project")
+        );
+    }
+
+    #[test]
+    fn ssh() {
+        assert_eq!(
+            GitRemote::parse("origin", "ssh://git@github.com:22/owner/repo.git"),
+            remote("github.com", "owner/repo")
+        );
+    }
+
+    #[test]
+    fn scp_like() {
+        assert_eq!(
+            GitRemote::parse("origin", "git@github.com:owner/repo.git"),
+            remote("github.com", "owner/repo")
+        );
+        assert_eq!(
+            GitRemote::parse("origin", "gitlab.com:group/project"),
+            remote("gitlab.com", "group/project")
+        );
+    }
+
+    #[test]
+    fn not_a_repository() {
+        assert_eq!(GitRemote::parse("origin", "/srv/git/repo.git"), None);
+        assert_eq!(GitRemote::parse("origin", "https://github.com/owner"), None);
+        assert_eq!(GitRemote::parse("origin", ":owner/repo"), None);
+    }
+
+    #[test]
+    fn matches_the_endpoint_and_path() {
+        let github = remote("github.com", "Owner/Repo").unwrap();
+        assert!(github.is_on("https://api.github.com"));
+        assert!(!github.is_on("https://gitlab.com"));
+        assert!(github.is_path("owner/repo"));
+        assert!(!github.is_path("owner/other"));
+    }
+}
@@ -0,0 +1,483 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use colored::Color;
+use reqwest::Client;
+use serde_yaml::{Mapping, Value};
+
+use crate::config::AppConfig;
+use crate::prompt::{ask, ask_required, choose, confirm};
+use crate::providers::common::credentials::{Credential, HasSecretToken};
+use crate::providers::github::{methods::list_accessible_repositories_github, model::GitHubConfig};
+use crate::providers::gitlab::{methods::list_accessible_projects_gitlab, model::GitLabConfig};
+use crate::providers::jira::{
+    methods::{find_open_issue_key_jira, get_jira_transitions, list_accessible_projects_jira},
+    model::JiraConfig,
+};
+
+/// offered, in turn, for each new issue store
+const COLORS: &[&str] = &["blue", "green", "magenta", "cyan", "yellow", "red"];
+
+fn map(entries: Vec<(&str, Value)>) -> Value {
+    let mut mapping = Mapping::new();
+    for (key, value) in entries {
+        mapping.insert(Value::from(key), value);
+    }
+    Value::Mapping(mapping)
+}
+
+/// The ids and colours handed out so far
+struct Stores {
+    ids: Vec<String>,
+}
+
+impl Stores {
+    /// A short unique id, suggested from the name of the repo/project
+    fn ask_id(&mut self, name: &str) -> Result<String> {
+        let suggestion = name
+            .rsplit('/')
+            .next()
+            .and_then(|n| n.chars().next())
+            .map(|c| c.to_uppercase().to_string())
+            .filter(|s| !self.ids.contains(s))
+            .unwrap_or_default();
+
+        loop {
+            let id = ask_required(&format!("  Short id for {}", name), &suggestion)?;
+            if id.contains('/') {
+                println!("  the id can't contain a '/'");
+            } else if self.ids.contains(&id) {
+                println!("  {} is already used, ids must be unique", id);
+            } else {
+                self.ids.push(id.clone());
+                return Ok(id);
+            }
+        }
+    }
+
+    fn ask_color(&self, name: &str) -> Result<String> {
+        let suggestion = COLORS[(self.ids.len() - 1) % COLORS.len()];
+        loop {
+            let color = ask_required(&format!("  Colour for {}", name), suggestion)?;
+            match Color::from_str(&color) {
+                Ok(_) => return Ok(color),
+                Err(_) => println!("  '{}' is not a colour, e.g. {}", color, COLORS.join(", ")),
+            }
+        }
+    }
+}
+
+/// How the provider's token will be read, see [Credential]
+fn ask_credential(service: &str, username_question: &str) -> Result<(Credential, Value)> {
+    println!("  Where is the API token kept? keyring, env, file or command");
+    let source = loop {
+        let source = ask("  Token source", "keyring")?;
+        if ["keyring", "env", "file", "command"].contains(&source.as_str()) {
+            break source;
+        }
+    };
+
+    let mut credential = Credential::default();
+    let mut entries = Vec::new();
+
+    match source.as_str() {
+        "env" => {
+            let var = ask_required("  Environment variable", "")?;
+            entries.push(("env", Value::from(var.clone())));
+            credential.env = Some(var);
+        }
+        "file" => {
+            let file = ask_required("  Token file", "")?;
+            entries.push(("file", Value::from(file.clone())));
+            credential.file = Some(file);
+        }
+        "command" => {
+            let command = ask_required("  Command that prints the token", "")?;
+            entries.push(("command", Value::from(command.clone())));
+            credential.command = Some(command);
+        }
+        _ => {
+            let service = ask_required("  Keyring service", service)?;
+            entries.push(("service", Value::from(service.clone())));
+            credential.service = Some(service);
+        }
+    }
+
+    // the keyring entry and Jira's login both need a username
+    if credential.service.is_some() || !username_question.is_empty() {
+        let question = match username_question {
+            "" => "  Keyring username",
+            q => q,
+        };
+        let username = ask_required(question, "")?;
+        entries.push(("username", Value::from(username.clone())));
+        credential.username = Some(username);
+    }
+
+    Ok((credential, map(entries)))
+}
+
+/// Offer the repositories/projects the token can see, when it can be read
+async fn discover(
+    what: &str,
+    token_available: bool,
+    list: impl std::future::Future<Output = Result<Vec<String>>>,
+) -> Result<Vec<String>> {
+    if !token_available || !confirm(&format!("  List the {} your token can see?", what), true)? {
+        return Ok(Vec::new());
+    }
+
+    match list.await {
+        Ok(items) if items.is_empty() => {
+            println!("  none were found");
+            Ok(Vec::new())
+        }
+        Ok(items) => choose(&items),
+        Err(e) => {
+            println!("  unable to list them, {:#}", e);
+            Ok(Vec::new())
+        }
+    }
+}
+
+/// Names typed in by hand, one per prompt, until a blank answer
+fn ask_names(question: &str, already: &[String]) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    if !already.is_empty() && !confirm("  Add more by hand?", false)? {
+        return Ok(names);
+    }
+    loop {
+        let name = ask(question, "")?;
+        if name.is_empty() {
+            return Ok(names);
+        }
+        names.push(name);
+    }
+}
+
+async fn add_github(client: &Client, stores: &mut Stores) -> Result<Value> {
+    let provider_id = ask_required("Provider id (used in messages)", "github.com")?;
+    let endpoint = ask_required("API endpoint", "https://api.github.com")?;
+    let (credential, credential_yaml) = ask_credential("github.com", "")?;
+
+    let config = GitHubConfig {
+        credential: Some(credential),
+        endpoint: endpoint.clone(),
+        provider_id: provider_id.clone(),
+        repositories: Vec::new(),
+    };
+    let token_available = config.get_token().await.is_ok();
+
+    let mut names = discover(
+        "repositories",
+        token_available,
+        list_accessible_repositories_github(client, &config),
+    )
+    .await?;
+    let typed = ask_names("  Repository (owner/repo), blank when done", &names)?;
+    names.extend(typed);
+
+    let mut repositories = Vec::new();
+    for name in names {
+        let Some((owner, repo)) = name.split_once('/') else {
+            println!("  {} is not owner/repo, skipped", name);
+            continue;
+        };
+        let id = stores.ask_id(&name)?;
+        let color = stores.ask_color(&name)?;
+        repositories.push(map(vec![
+            ("id", Value::from(id)),
+            ("color", Value::from(color)),
+            ("owner", Value::from(owner)),
+            ("repo", Value::from(repo)),
+        ]));
+    }
+
+    Ok(map(vec![
+        ("provider_id", Value::from(provider_id)),
+        ("endpoint", Value::from(endpoint)),
+        ("credential", credential_yaml),
+        ("repositories", Value::Sequence(repositories)),
+    ]))
+}
+
+async fn add_gitlab(client: &Client, stores: &mut Stores) -> Result<Value> {
+    let provider_id = ask_required("Provider id (used in messages)", "gitlab.com")?;
+    let endpoint = ask_required("Endpoint", "https://gitlab.com")?;
+    let (credential, credential_yaml) = ask_credential("gitlab.com", "")?;
+
+    let config = GitLabConfig {
+        credential: Some(credential),
+        endpoint: endpoint.clone(),
+        provider_id: provider_id.clone(),
+        repositories: Vec::new(),
+    };
+    let token_available = config.get_token().await.is_ok();
+
+    let mut names = discover(
+        "projects",
+        token_available,
+        list_accessible_projects_gitlab(client, &config),
+    )
+    .await?;
+    let typed = ask_names(
+        "  Project (group/project) or project number, blank when done",
+        &names,
+    )?;
+    names.extend(typed);
+
+    let mut repositories = Vec::new();
+    for name in names {
+        let id = stores.ask_id(&name)?;
+        let color = stores.ask_color(&name)?;
+        repositories.push(map(vec![
+            ("id", Value::from(id)),
+            ("color", Value::from(color)),
+            // the API takes the path with its /'s encoded
+            ("project_id", Value::from(name.replace('/', "%2F"))),
+        ]));
+    }
+
+    Ok(map(vec![
+        ("provider_id", Value::from(provider_id)),
+        ("endpoint", Value::from(endpoint)),
+        ("credential", credential_yaml),
+        ("repositories", Value::Sequence(repositories)),
+    ]))
+}
+
+/// The transition to close issues with, suggested from the workflow of an open issue
+async fn ask_close_transition(
+    client: &Client,
+    config: &JiraConfig,
+    project_key: &str,
+) -> Result<String> {
+    let issue = find_open_issue_key_jira(client, config, project_key).await;
+    let transitions = match issue {
+        Ok(Some(key)) => get_jira_transitions(client, config, &key).await.ok(),
+        _ => None,
+    };
+
+    let suggestion = match &transitions {
+        Some(transitions) => {
+            println!("  The transitions of {}:", project_key);
+            for t in transitions {
+                println!("    {:>4}  {} -> {}", t.id, t.name, t.to.name);
+            }
+            transitions
+                .iter()
+                .find(|t| {
+                    t.to.status_category
+                        .as_ref()
+                        .is_some_and(|c| c.key == "done")
+                })
+                .map(|t| t.id.clone())
+                .unwrap_or_default()
+        }
+        None => {
+            println!("  (run `tskmstr jira-transitions <ISSUE-KEY>` to see the transition ids)");
+            String::new()
+        }
+    };
+
+    // close finds the transition to a Done status itself, this is only its fallback
+    ask(
+        "  Transition id that closes an issue (blank to use the workflow's Done transition)",
+        &suggestion,
+    )
+}
+
+async fn add_jira(client: &Client, stores: &mut Stores) -> Result<Value> {
+    let provider_id = ask_required("Provider id (used in messages)", "jira")?;
+    let endpoint = ask_required("Jira URL, e.g. https://yours.atlassian.net", "")?;
+    let host = endpoint
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_end_matches('/')
+        .to_string();
+    let (credential, credential_yaml) = ask_credential(&host, "  Jira login (e-mail)")?;
+
+    let config = JiraConfig {
+        credential: Some(credential),
+        endpoint: endpoint.trim_end_matches('/').to_string(),
+        provider_id: provider_id.clone(),
+        projects: Vec::new(),
+    };
+    let token_available = config.get_token().await.is_ok();
+
+    let listed = async {
+        Ok(list_accessible_projects_jira(client, &config)
+            .await?
+            .into_iter()
+            .map(|p| format!("{} {}", p.key, p.name))
+            .collect())
+    };
+    let mut keys: Vec<String> = discover("projects", token_available, listed)
+        .await?
+        .into_iter()
+        .filter_map(|p| p.split_whitespace().next().map(|k| k.to_string()))
+        .collect();
+    let typed = ask_names("  Project key, blank when done", &keys)?;
+    keys.extend(typed);
+
+    let mut projects = Vec::new();
+    for key in keys {
+        let id = stores.ask_id(&key)?;
+        let color = stores.ask_color(&key)?;
+        let issue_type = ask_required("  Issue type of new issues", "Task")?;
+        let close_transition_id = match token_available {
+            true => ask_close_transition(client, &config, &key).await?,
+            false => ask(
+                "  Transition id that closes an issue (blank to use the workflow's Done transition)",
+                "",
+            )?,
+        };
+        let mut project = vec![
+            ("id", Value::from(id)),
+            ("color", Value::from(color)),
+            ("project_key", Value::from(key)),
+            ("default_issue_type", Value::from(issue_type)),
+        ];
+        if !close_transition_id.is_empty() {
+            project.push(("close_transition_id", Value::from(close_transition_id)));
+        }
+        projects.push(map(project));
+    }
+
+    Ok(map(vec![
+        ("provider_id", Value::from(provider_id)),
+        ("endpoint", Value::from(config.endpoint.clone())),
+        ("credential", credential_yaml),
+        ("projects", Value::Sequence(projects)),
+    ]))
+}
+
+/// Mark the chosen store `defaults: { for_new_tasks: true }`
+fn set_default_store(providers: &mut [(&str, Value)], id: &str) {
+    for (_, provider) in providers.iter_mut() {
+        for key in ["repositories", "projects"] {
+            let Some(Value::Sequence(stores)) = provider.get_mut(key) else {
+                continue;
+            };
+            for store in stores.iter_mut() {
+                if store.get("id").and_then(Value::as_str) == Some(id) {
+                    if let Value::Mapping(m) = store {
+                        m.insert(
+                            Value::from("defaults"),
+                            map(vec![("for_new_tasks", Value::from(true))]),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// `init`: ask about the providers, and write the config file
+pub async fn init_config(config_file: &Path, force: bool) -> Result<()> {
+    println!("This writes a new config to {}", config_file.display());
+
+    if config_file.exists()
+        && !force
+        && !confirm(
+            &format!("{} already exists, overwrite it?", config_file.display()),
+            false,
+        )?
+    {
+        return Err(anyhow!(
+            "{} already exists, it was not overwritten",
+            config_file.display()
+        ));
+    }
+
+    let client = Client::new();
+    let mut stores = Stores { ids: Vec::new() };
+    let mut providers: Vec<(&str, Value)> = Vec::new();
+
+    loop {
+        let kind = ask(
+            "Add a provider: github, gitlab or jira (blank when done)",
+            "",
+        )?;
+        let provider = match kind.as_str() {
+            "" => break,
+            "github" => ("github.com", add_github(&client, &mut stores).await?),
+            "gitlab" => ("gitlab.com", add_gitlab(&client, &mut stores).await?),
+            "jira" => ("jira", add_jira(&client, &mut stores).await?),
+            other => {
+                println!("'{}' is not github, gitlab or jira", other);
+                continue;
+            }
+        };
+        providers.push(provider);
+    }
+
+    if stores.ids.is_empty() {
+        return Err(anyhow!("no issue stores were added, nothing was written"));
+    }
+
+    let default_id = loop {
+        let id = ask_required("Which issue store are new tasks added to", &stores.ids[0])?;
+        if stores.ids.contains(&id) {
+            break id;
+        }
+        println!("{} is not one of {}", id, stores.ids.join(", "));
+    };
+    set_default_store(&mut providers, &default_id);
+
+    let priority_labels = ask("Priority labels (comma separated)", "urgent, todo")?;
+
+    let mut config = vec![
+        (
+            "colors",
+            map(vec![
+                ("issue_id", Value::from("bright red")),
+                ("title", Value::from("blue")),
+                ("tags", Value::from("bright green")),
+            ]),
+        ),
+        (
+            "labels",
+            map(vec![(
+                "priority_labels",
+                Value::Sequence(
+                    priority_labels
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|l| !l.is_empty())
+                        .map(Value::from)
+                        .collect(),
+                ),
+            )]),
+        ),
+    ];
+
+    // one list per provider kind, in the order they were first added
+    for (kind, provider) in providers {
+        match config.iter_mut().find(|(k, _)| *k == kind) {
+            Some((_, Value::Sequence(list))) => list.push(provider),
+            _ => config.push((kind, Value::Sequence(vec![provider]))),
+        }
+    }
+
+    let yaml = serde_yaml::to_string(&map(config))?;
+
+    // what is written has to load
+    serde_yaml::from_str::<AppConfig>(&yaml).map_err(|e| {
+        anyhow!(
+            "the generated config does not load, it was not written: {}",
+            e
+        )
+    })?;
+
+    if let Some(dir) = config_file.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(config_file, yaml)?;
+
+    println!("Wrote {}", config_file.display());
+    println!("Next, save the API tokens with `tskmstr auth login <provider_id>` (for keyring and file credentials),");
+    println!("then `tskmstr auth status` and `tskmstr config check`");
+    Ok(())
+}
@@ -1,23 +1,39 @@
-#![feature(fn_traits)]
-#![feature(unboxed_closures)]
-
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 #[allow(unused_imports)]
 use log::{debug, error, info, warn};
 
 use clap::{Parser, Subcommand};
-use providers::jira::methods::list_jira_transition_ids;
+use providers::common::model::{Assignee, CloseReason, Closure, WorkStatus};
 
+mod auth;
+mod cache;
 mod config;
+mod config_check;
+mod config_layers;
 mod control;
+mod duration;
+mod editor;
+mod errors;
+mod git_remote;
+mod init;
 mod output;
+mod prompt;
 mod providers;
+mod template;
 
+use auth::{auth_login, auth_status};
+use cache::CachePolicy;
 use config::AppConfig;
+use config_check::check_config_file;
+use config_layers::{load_layered_config, show_config};
 use control::*;
-use std::{collections::HashSet, path::PathBuf, str::FromStr};
+use duration::parse_duration;
+use init::init_config;
+use std::{collections::HashSet, path::PathBuf, str::FromStr, time::Duration};
 
-use output::{aggregate_and_display_all_tasks, list_issue_stores};
+use output::{
+    aggregate_and_display_all_tasks, display_closed_tasks, list_issue_stores, GroupBy, OutputFormat,
+};
 
 use directories::ProjectDirs;
 
@@ -44,10 +60,10 @@ enum Command {
     /// Add a new issue to the default repository
     Add {
         /// The title of your issue/task
-        title: String,
+        title: Option<String>,
 
-        /// Details of the issue
-        details: String,
+        /// Details of the issue; without them, the issue is written in $EDITOR
+        details: Option<String>,
 
         /// Tags/Labels to apply to the issue/task
         tags: Option<Vec<String>>,
@@ -55,11 +71,46 @@ enum Command {
         /// Limit the activity to one issue/task repository
         #[arg(short, long)]
         issue_store_id: Option<String>,
+
+        /// Assign it to a user, `@me` being you
+        #[arg(long, value_name = "USER")]
+        assign: Option<Assignee>,
+    },
+
+    /// Show an issue/task in full, with its comments
+    Show {
+        /// ID of the issue/task to show
+        id: String,
     },
 
     /// Close a task
     Close(CloseCommand),
 
+    /// Reopen a closed task
+    Reopen {
+        /// ID of the issue/task to reopen
+        id: String,
+    },
+
+    /// List the most recently closed tasks, to find one to reopen
+    Closed {
+        /// Limit the activity to one issue/task provider
+        #[arg(short, long)]
+        issue_store_id: Option<String>,
+
+        /// How many to show
+        #[arg(short = 'n', long, default_value_t = 10)]
+        limit: usize,
+
+        /// Show all details
+        #[arg(short, long)]
+        all: bool,
+
+        /// Output format, json, ndjson, csv and yaml are for scripts
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+
     /// Add a comment/note to a issue/task
     Comment(CommentCommand),
 
@@ -68,10 +119,90 @@ enum Command {
     Tags(TagsCommand),
 
     /// List issue/task stores
-    IssueStores,
+    IssueStores {
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+
+    /// Write a new config file, asking about the providers and their repositories/projects
+    Init {
+        /// Overwrite an existing config file without asking
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Save and check the API tokens of the providers
+    #[command(subcommand)]
+    Auth(AuthCommand),
+
+    /// Check the config file
+    #[command(subcommand)]
+    Config(ConfigCommand),
+
+    /// Move a Jira issue to a status (or along a transition), by name
+    Move {
+        /// ID of the issue/task to move, e.g. J/KAN-3
+        id: String,
+
+        /// The status, or transition, e.g. "In Progress"; without it the transitions are listed
+        to: Option<String>,
+    },
+
+    /// Change the title and/or body of a task; with neither, edit them in $EDITOR
+    Edit {
+        /// ID of the issue/task to edit
+        id: String,
+
+        /// The new title
+        #[arg(short, long)]
+        title: Option<String>,
+
+        /// The new body/description
+        #[arg(short, long)]
+        body: Option<String>,
+    },
+
+    /// Assign a task to a user, or to yourself
+    Assign {
+        /// ID of the issue/task to assign
+        id: String,
+
+        /// A GitHub login, GitLab username, or Jira e-mail or name
+        #[arg(required_unless_present = "me", conflicts_with = "me")]
+        user: Option<Assignee>,
+
+        /// Assign it to yourself, the user of the issue store's token
+        #[arg(long)]
+        me: bool,
+    },
+
+    /// Unassign a user (or yourself) from a task; without either, everyone assigned to it
+    Unassign {
+        /// ID of the issue/task to unassign
+        id: String,
+
+        /// A GitHub login, GitLab username, or Jira e-mail or name
+        #[arg(conflicts_with = "me")]
+        user: Option<Assignee>,
+
+        /// Unassign yourself, the user of the issue store's token
+        #[arg(long)]
+        me: bool,
+    },
+
+    /// Set a task to todo, doing or done, as the issue store's `status:` maps them
+    Status {
+        /// ID of the issue/task, e.g. G/42 or J/KAN-3
+        id: String,
+
+        #[arg(value_enum)]
+        status: WorkStatus,
+    },
 
     /// Show Jira Transitions allowed for a given ID
     JiraTransitions {
+        /// e.g. J/KAN-3, or just KAN-3
         id: String,
     },
 
@@ -81,9 +212,29 @@ enum Command {
         #[arg(short, long)]
         issue_store_id: Option<String>,
 
+        /// Only list the issue stores of the git repository in the current directory
+        #[arg(long, conflicts_with = "issue_store_id")]
+        here: bool,
+
         /// Show all details
         #[arg(short, long)]
         all: bool,
+
+        /// Only show the cached issues from the last listing, do not contact any provider
+        #[arg(long, conflicts_with = "max_age")]
+        offline: bool,
+
+        /// Use the cached issues of a store when they are younger than this (e.g. 10m, 2h, 1d)
+        #[arg(long, value_parser = parse_duration)]
+        max_age: Option<Duration>,
+
+        /// Output format, json, ndjson, csv and yaml are for scripts
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Group the text listing by labels, or by todo/doing/done
+        #[arg(short, long, value_enum, default_value_t = GroupBy::Labels)]
+        group_by: GroupBy,
     },
 }
 
@@ -91,21 +242,33 @@ enum Command {
 struct CloseCommand {
     /// ID of the issue/task to close
     id: String,
+
+    /// Why it is closed; GitHub's state_reason, a GitLab note or the Jira resolution
+    #[arg(long, value_enum)]
+    reason: Option<CloseReason>,
+
+    /// A comment to add as it is closed
+    #[arg(long)]
+    comment: Option<String>,
 }
 
 #[derive(Debug, clap::Args)]
 struct CommentCommand {
     /// ID of the issue/task to add a comment to
     id: String,
-    /// New Comment to add to the issue/task
-    comment: String,
+    /// New Comment to add to the issue/task; without it, it is written in $EDITOR
+    comment: Option<String>,
 }
 
 impl FromStr for CloseCommand {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(CloseCommand { id: s.to_string() })
+        Ok(CloseCommand {
+            id: s.to_string(),
+            reason: None,
+            comment: None,
+        })
     }
 }
 
@@ -118,6 +281,32 @@ enum TagsCommand {
     Remove(TagOperationParameters),
 }
 
+#[derive(Subcommand, Debug)]
+enum ConfigCommand {
+    /// Report duplicate ids, defaults, colours, credentials and filters that are wrong
+    Check,
+
+    /// Print the config file, or with --resolved the config after includes,
+    /// the project's .tskmstr.yml and TSKMSTR_* variables are merged
+    Show {
+        /// Print the merged config, and where it came from
+        #[arg(long)]
+        resolved: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum AuthCommand {
+    /// Prompt for a provider's API token, and save it to the credential's file or keyring entry
+    Login {
+        /// The provider_id from the config
+        provider_id: String,
+    },
+
+    /// Show who each provider's token authenticates as, with its scopes and expiry
+    Status,
+}
+
 #[derive(clap::Args, Debug)]
 struct TagOperationParameters {
     /// ID of the task (must be prefixed with the provider id e.g. P-888, or J-ID-999)
@@ -144,9 +333,29 @@ async fn do_work(args: &Cli, config: &AppConfig) -> Result<(), anyhow::Error> {
             details,
             tags,
             issue_store_id,
-        }) => add_new_task(issue_store_id, config, title, details, tags).await?,
+            assign,
+        }) => add_new_task(issue_store_id, config, title, details, tags, assign).await?,
+        Some(Command::Show { id }) => {
+            show_task(config, id.clone()).await?;
+        }
         Some(Command::Close(close_cmd)) => {
-            close_task(config, close_cmd.id.clone()).await?;
+            let closure = Closure {
+                reason: close_cmd.reason,
+                comment: close_cmd.comment.clone(),
+            };
+            close_task(config, close_cmd.id.clone(), &closure).await?;
+        }
+        Some(Command::Reopen { id }) => {
+            reopen_task(config, id.clone()).await?;
+        }
+        Some(Command::Closed {
+            issue_store_id,
+            limit,
+            all,
+            format,
+        }) => {
+            let store_ids: Vec<String> = issue_store_id.iter().cloned().collect();
+            display_closed_tasks(&store_ids, config, *limit, all, format).await?;
         }
         Some(Command::Comment(comment_cmd)) => {
             comment_task(config, comment_cmd.id.clone(), comment_cmd.comment.clone()).await?;
@@ -159,42 +368,135 @@ async fn do_work(args: &Cli, config: &AppConfig) -> Result<(), anyhow::Error> {
             let tag_set: &HashSet<String> = &tag_removals.tags.clone().into_iter().collect();
             remove_tags_from_task(config, tag_removals.id.clone(), tag_set).await?;
         }
-        Some(Command::IssueStores) => {
-            list_issue_stores(config).await?;
+        Some(Command::IssueStores { format }) => {
+            list_issue_stores(config, format).await?;
+        }
+        Some(Command::Auth(AuthCommand::Login { provider_id })) => {
+            auth_login(config, provider_id).await?;
+        }
+        Some(Command::Auth(AuthCommand::Status)) => {
+            auth_status(config).await?;
+        }
+        Some(Command::Config(_)) | Some(Command::Init { .. }) => {
+            // these run before (without) the config being loaded, see main
+        }
+        Some(Command::Move { id, to }) => {
+            move_task(config, id, to.as_deref()).await?;
+        }
+        Some(Command::Edit { id, title, body }) => {
+            edit_task(config, id.clone(), title, body).await?;
+        }
+        Some(Command::Assign { id, user, me }) => {
+            let assignee = match me {
+                true => Assignee::Me,
+                false => user.clone().ok_or_else(|| anyhow!("a user, or --me"))?,
+            };
+            assign_task(config, id.clone(), &assignee).await?;
+        }
+        Some(Command::Unassign { id, user, me }) => {
+            let assignee = match me {
+                true => Some(Assignee::Me),
+                false => user.clone(),
+            };
+            unassign_task(config, id.clone(), assignee.as_ref()).await?;
+        }
+        Some(Command::Status { id, status }) => {
+            set_task_status(config, id.clone(), *status).await?;
         }
         Some(Command::JiraTransitions { id }) => {
-            list_jira_transition_ids(&config.jira[0], id).await?;
+            move_task(config, id, None).await?;
         }
         Some(Command::List {
             issue_store_id,
+            here,
             all,
-        }) => aggregate_and_display_all_tasks(issue_store_id, config, colors, &all).await?,
-        None => aggregate_and_display_all_tasks(&None, config, colors, &false).await?,
+            offline,
+            max_age,
+            format,
+            group_by,
+        }) => {
+            let cache_policy = match (offline, max_age) {
+                (true, _) => CachePolicy::Offline,
+                (false, Some(max_age)) => CachePolicy::MaxAge(*max_age),
+                (false, None) => CachePolicy::Refresh,
+            };
+            let store_ids: Vec<String> = match here {
+                true => config
+                    .find_stores_here()?
+                    .iter()
+                    .map(|store| store.store_id())
+                    .collect(),
+                false => issue_store_id.iter().cloned().collect(),
+            };
+            aggregate_and_display_all_tasks(
+                &store_ids,
+                config,
+                colors,
+                all,
+                &cache_policy,
+                format,
+                group_by,
+            )
+            .await?
+        }
+        None => {
+            aggregate_and_display_all_tasks(
+                &[],
+                config,
+                colors,
+                &false,
+                &CachePolicy::Refresh,
+                &OutputFormat::Text,
+                &GroupBy::Labels,
+            )
+            .await?
+        }
     };
 
     Ok(())
 }
 
+fn config_file(args: &Cli) -> Result<PathBuf, anyhow::Error> {
+    match &args.config {
+        Some(x) => Ok(PathBuf::from(x)),
+        None => {
+            let proj_dirs = ProjectDirs::from("org", "inosion", "tskmstr")
+                .ok_or_else(|| anyhow!("No Config directory found"))?;
+            Ok(proj_dirs.config_dir().join("tskmstr.config.yml"))
+        }
+    }
+}
+
+fn load_config(args: &Cli) -> Result<AppConfig, anyhow::Error> {
+    // Read the repository configuration from YAML, with its layers
+    let config_file = config_file(args)?;
+    Ok(load_layered_config(&config_file)?.config)
+}
+
 #[tokio::main]
-async fn main() -> Result<(), anyhow::Error> {
+async fn main() {
     let args = Cli::parse();
-    // Read the repository configuration from YAML
 
-    let config_file = match &args.config {
-        Some(x) => PathBuf::from(x).as_os_str().to_owned(),
-        None => {
-            let proj_dirs = ProjectDirs::from("org", "inosion", "tskmstr")
-                .unwrap_or_else(|| panic!("No Config directory found"));
-            let mut config_dir = proj_dirs.config_dir().as_os_str().to_owned();
-            config_dir.push("/tskmstr.config.yml");
-            config_dir
+    let result = match &args.cmd {
+        // the config can't be loaded to check it, when it has problems
+        Some(Command::Config(ConfigCommand::Check)) => {
+            config_file(&args).and_then(|file| check_config_file(&file))
         }
+        Some(Command::Config(ConfigCommand::Show { resolved })) => {
+            config_file(&args).and_then(|file| show_config(&file, *resolved))
+        }
+        Some(Command::Init { force }) => match config_file(&args) {
+            Ok(file) => init_config(&file, *force).await,
+            Err(e) => Err(e),
+        },
+        _ => match load_config(&args) {
+            Ok(config) => do_work(&args, &config).await,
+            Err(e) => Err(e),
+        },
     };
-    let filename = config_file.clone().into_string().unwrap();
 
-    let contents = std::fs::read_to_string(&config_file)
-        .expect(format!("Failed to open file {}", filename).as_str());
-    let config: AppConfig = serde_yaml::from_str(&contents)
-        .expect(format!("Failed to load file {}", filename).as_str());
-    do_work(&args, &config).await
+    if let Err(e) = result {
+        eprintln!("Error: {:#}", e);
+        std::process::exit(errors::exit_code(&e));
+    }
 }
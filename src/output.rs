@@ -1,33 +1,231 @@
-use crate::config::Colors;
-use crate::providers::github::methods::collect_tasks_from_github;
+use crate::cache::{describe_age, CachePolicy, IssueCache};
+use crate::config::{Colors, LabelConfig};
+use crate::errors::TskmstrError;
+use crate::providers::common::provider::IssueProvider;
+use crate::{
+    config::AppConfig,
+    providers::common::model::{Issue, IssueDetails, WorkStatus},
+};
+use anyhow::anyhow;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use colored::Colorize;
+use futures::stream::{self, StreamExt};
+use log::{debug, warn};
+use serde::Serialize;
+use std::time::Duration;
 
-use crate::providers::gitlab::methods::collect_tasks_from_gitlab;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-use crate::providers::jira::methods::collect_tasks_from_jira;
-use crate::{config::AppConfig, providers::common::model::Issue};
-use colored::{Color, Colorize};
-use std::str::FromStr;
+/// How `list` and `issue-stores` print their results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// coloured, grouped by tags (the default)
+    Text,
+    /// a JSON array
+    Json,
+    /// one JSON object per line
+    Ndjson,
+    Csv,
+    Yaml,
+}
+
+/// How the `text` listing groups the issues
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum GroupBy {
+    /// priority first, then by their other labels (the default)
+    Labels,
+    /// to do, doing and done, as each store's `status:` maps them
+    Status,
+}
+
+/// Something printed in one of the machine readable formats.
+/// The serialized field names are the schema scripts rely on, so only ever add to them.
+trait Record: Serialize {
+    fn csv_header() -> &'static [&'static str];
+    fn csv_row(&self) -> Vec<String>;
+}
+
+#[derive(Serialize)]
+struct IssueRecord<'a> {
+    id: &'a str,
+    store_id: &'a str,
+    provider: &'a str,
+    title: &'a str,
+    url: &'a str,
+    tags: Vec<&'a str>,
+    state: &'a str,
+    assignees: &'a [String],
+    author: Option<&'a str>,
+    created_at: Option<DateTime<Utc>>,
+    updated_at: Option<DateTime<Utc>>,
+    due: Option<NaiveDate>,
+    milestone: Option<&'a str>,
+    description: Option<&'a str>,
+    closed_at: Option<DateTime<Utc>>,
+    status: Option<WorkStatus>,
+}
+
+impl<'a> From<&'a Issue> for IssueRecord<'a> {
+    fn from(issue: &'a Issue) -> Self {
+        IssueRecord {
+            id: &issue.id,
+            store_id: &issue.store_id,
+            provider: &issue.provider,
+            title: &issue.title,
+            url: &issue.html_url,
+            tags: issue.tags.iter().map(|t| t.name.as_str()).collect(),
+            state: &issue.state,
+            assignees: &issue.assignees,
+            author: issue.author.as_deref(),
+            created_at: issue.created_at,
+            updated_at: issue.updated_at,
+            due: issue.due,
+            milestone: issue.milestone.as_deref(),
+            description: issue.description.as_deref(),
+            closed_at: issue.closed_at,
+            status: issue.status,
+        }
+    }
+}
 
-use std::collections::{HashMap, HashSet};
+impl Record for IssueRecord<'_> {
+    fn csv_header() -> &'static [&'static str] {
+        &[
+            "id",
+            "store_id",
+            "provider",
+            "title",
+            "url",
+            "tags",
+            "state",
+            "assignees",
+            "author",
+            "created_at",
+            "updated_at",
+            "due",
+            "milestone",
+            "description",
+            "closed_at",
+            "status",
+        ]
+    }
+
+    fn csv_row(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(),
+            self.store_id.to_string(),
+            self.provider.to_string(),
+            self.title.to_string(),
+            self.url.to_string(),
+            self.tags.join(","),
+            self.state.to_string(),
+            self.assignees.join(","),
+            self.author.unwrap_or_default().to_string(),
+            self.created_at.map(|t| t.to_rfc3339()).unwrap_or_default(),
+            self.updated_at.map(|t| t.to_rfc3339()).unwrap_or_default(),
+            self.due.map(|d| d.to_string()).unwrap_or_default(),
+            self.milestone.unwrap_or_default().to_string(),
+            self.description.unwrap_or_default().to_string(),
+            self.closed_at.map(|t| t.to_rfc3339()).unwrap_or_default(),
+            self.status.map(|s| s.to_string()).unwrap_or_default(),
+        ]
+    }
+}
+
+#[derive(Serialize)]
+struct IssueStoreRecord {
+    id: String,
+    provider: String,
+    location: String,
+}
+
+impl Record for IssueStoreRecord {
+    fn csv_header() -> &'static [&'static str] {
+        &["id", "provider", "location"]
+    }
+
+    fn csv_row(&self) -> Vec<String> {
+        vec![
+            self.id.clone(),
+            self.provider.clone(),
+            self.location.clone(),
+        ]
+    }
+}
+
+fn print_records<T: Record>(records: &[T], format: &OutputFormat) -> Result<(), anyhow::Error> {
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(records)?),
+        OutputFormat::Ndjson => {
+            for record in records {
+                println!("{}", serde_json::to_string(record)?);
+            }
+        }
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(records)?),
+        OutputFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(std::io::stdout());
+            writer.write_record(T::csv_header())?;
+            for record in records {
+                writer.write_record(record.csv_row())?;
+            }
+            writer.flush()?;
+        }
+        OutputFormat::Text => unreachable!("text output is not a record format"),
+    }
+    Ok(())
+}
+
+/// What puts an issue into the Priority group: one of the priority labels,
+/// or a due date that falls inside the priority timeframe
+pub struct PriorityRules<'a> {
+    pub labels: &'a HashSet<String>,
+
+    /// the configured timeframe, as written (e.g. 48hrs)
+    pub timeframe: Option<&'a str>,
+
+    /// the last day an issue can be due, and still be a priority
+    pub due_by: Option<NaiveDate>,
+}
+
+impl<'a> PriorityRules<'a> {
+    pub fn from_config(labels: &'a LabelConfig) -> Result<PriorityRules<'a>, anyhow::Error> {
+        // further out than a date can be, every due date is inside it
+        let due_by = labels.priority_window()?.map(|window| {
+            chrono::Duration::from_std(window)
+                .ok()
+                .and_then(|window| Local::now().checked_add_signed(window))
+                .map_or(NaiveDate::MAX, |due_by| due_by.date_naive())
+        });
+
+        Ok(PriorityRules {
+            labels: &labels.priority_labels,
+            timeframe: labels.priority_timeframe.as_deref(),
+            due_by,
+        })
+    }
+
+    fn is_priority(&self, issue: &Issue) -> bool {
+        issue.tags.iter().any(|tag| self.labels.contains(&tag.name))
+            || self
+                .due_by
+                .zip(issue.due)
+                .is_some_and(|(due_by, due)| due <= due_by)
+    }
+}
 
-// Function to group tasks by labels, excluding priority labels
+// Function to group tasks by labels, excluding priority tasks
 fn group_tasks_by_labels(
-    issues: &Vec<Issue>,
-    priority_labels: &HashSet<String>,
+    issues: &[Issue],
+    priority: &PriorityRules,
 ) -> HashMap<String, Vec<Issue>> {
     issues
         .iter()
-        .filter(|issue| {
-            issue
-                .tags
-                .iter()
-                .all(|tag| !priority_labels.contains(&tag.name))
-        })
+        .filter(|issue| !priority.is_priority(issue))
         .fold(HashMap::new(), |mut acc, issue| {
             let labels_except_priority: Vec<String> = issue
                 .tags
                 .iter()
-                .filter(|tag| !priority_labels.contains(&tag.name))
+                .filter(|tag| !priority.labels.contains(&tag.name))
                 .map(|tag| tag.name.clone())
                 .collect();
 
@@ -42,30 +240,58 @@ fn group_tasks_by_labels(
         })
 }
 
-fn group_tasks_by_priority_labels(
-    issues: &Vec<Issue>,
-    priority_labels: &HashSet<String>,
-) -> Vec<Issue> {
-    issues
+/// the priority tasks, soonest due (and so overdue) first
+fn group_tasks_by_priority(issues: &[Issue], priority: &PriorityRules) -> Vec<Issue> {
+    let mut priority_tasks: Vec<Issue> = issues
         .iter()
-        .filter(|issue| {
-            issue
-                .tags
-                .iter()
-                .any(|tag| priority_labels.contains(&tag.name))
-        })
+        .filter(|issue| priority.is_priority(issue))
         .cloned()
-        .collect()
+        .collect();
+    priority_tasks.sort_by_key(|issue| (issue.due.is_none(), issue.due));
+    priority_tasks
+}
+
+fn print_issue_line(issue: &Issue, colors: &Colors, all: &bool, today: NaiveDate) {
+    let tags = format!(
+        "({})",
+        issue
+            .tags
+            .iter()
+            .map(|t| t.name.clone())
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+
+    let due = match issue.due {
+        Some(due) if due < today => format!(" [overdue {}]", due).red().bold().to_string(),
+        Some(due) => format!(" [due {}]", due),
+        None => "".to_string(),
+    };
+
+    let details = match all {
+        false => "".to_string(),
+        true => format!(" - {}", issue.html_url),
+    };
+
+    println!(
+        " - {} {} {}{}{}",
+        issue.id.color(colors.issue_id()),
+        issue.title.color(colors.title()),
+        tags.color(colors.tags()),
+        due,
+        details
+    );
 }
 
 pub fn display_tasks_in_table(
-    issues: &Vec<Issue>,
+    issues: &[Issue],
     colors: &Colors,
-    priority_labels: &HashSet<String>,
+    priority: &PriorityRules,
     all: &bool,
 ) -> Result<(), anyhow::Error> {
-    let mut grouped_tasks = group_tasks_by_labels(issues, priority_labels);
-    let priority_tasks = group_tasks_by_priority_labels(issues, priority_labels);
+    let mut grouped_tasks = group_tasks_by_labels(issues, priority);
+    let priority_tasks = group_tasks_by_priority(issues, priority);
+    let today = Local::now().date_naive();
 
     // Sort the groups by priority, moving the priority group to the front
     grouped_tasks.remove(""); // Remove the empty key
@@ -81,39 +307,23 @@ pub fn display_tasks_in_table(
     });
 
     // Display priority tasks
-    let priority_labels_str = &priority_labels
+    let priority_labels_str = &priority
+        .labels
         .iter()
         .cloned()
         .collect::<Vec<String>>()
         .join(", ");
+    let timeframe_str = priority
+        .timeframe
+        .map_or("".to_string(), |t| format!(" (or due within {})", t));
     println!(
-        "Priority: {}",
-        priority_labels_str.color(Color::from_str(&colors.tags).unwrap())
+        "Priority: {}{}",
+        priority_labels_str.color(colors.tags()),
+        timeframe_str
     );
     println!("{:-<40}", "-"); // Divider line
     for issue in &priority_tasks {
-        let tags = format!(
-            "({})",
-            issue
-                .tags
-                .iter()
-                .map(|t| t.name.clone())
-                .collect::<Vec<String>>()
-                .join(", ")
-        );
-
-        let details = match all { 
-            false => "".to_string(),
-            true => format!(" - {}",issue.html_url)
-        };
-
-        println!(
-            " - {} {} {}{}",
-            issue.id.color(Color::from_str(&colors.issue_id).unwrap()),
-            issue.title.color(Color::from_str(&colors.title).unwrap()),
-            tags.color(Color::from_str(&colors.tags).unwrap()),
-            details
-        );
+        print_issue_line(issue, colors, all, today);
     }
 
     println!();
@@ -121,33 +331,10 @@ pub fn display_tasks_in_table(
     // Display grouped tasks
     for (group, group_issues) in groups {
         if !group.is_empty() {
-            println!(
-                "Tag: {}",
-                group.color(Color::from_str(&colors.tags).unwrap())
-            );
+            println!("Tag: {}", group.color(colors.tags()));
             println!("{:-<40}", "-"); // Divider line
             for issue in group_issues {
-                let tags = format!(
-                    "({})",
-                    issue
-                        .tags
-                        .iter()
-                        .map(|t| t.name.clone())
-                        .collect::<Vec<String>>()
-                        .join(", ")
-                );
-                let details = match all { 
-                    false => "".to_string(),
-                    true => format!(" - {}",issue.html_url)
-                };
-
-                println!(
-                    " - {} {} {}{}",
-                    issue.id.color(Color::from_str(&colors.issue_id).unwrap()),
-                    issue.title.color(Color::from_str(&colors.title).unwrap()),
-                    tags.color(Color::from_str(&colors.tags).unwrap()),
-                    details
-                );
+                print_issue_line(issue, colors, all, today);
             }
             println!();
         }
@@ -156,46 +343,342 @@ pub fn display_tasks_in_table(
     Ok(())
 }
 
+/// To do, doing and done, in that order; issues cached before statuses were read are to do
+pub fn display_tasks_by_status(
+    issues: &[Issue],
+    colors: &Colors,
+    all: &bool,
+) -> Result<(), anyhow::Error> {
+    let today = Local::now().date_naive();
+
+    let mut grouped: BTreeMap<WorkStatus, Vec<&Issue>> = BTreeMap::new();
+    for issue in issues {
+        let status = issue.status.unwrap_or(WorkStatus::Todo);
+        grouped.entry(status).or_default().push(issue);
+    }
+
+    for (status, group_issues) in grouped {
+        let heading = match status {
+            WorkStatus::Todo => "To do",
+            WorkStatus::Doing => "Doing",
+            WorkStatus::Done => "Done",
+        };
+        println!("Status: {}", heading.color(colors.tags()));
+        println!("{:-<40}", "-"); // Divider line
+        for issue in group_issues {
+            print_issue_line(issue, colors, all, today);
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+/// in the local timezone, to the minute
+fn format_timestamp(timestamp: &Option<DateTime<Utc>>) -> String {
+    timestamp
+        .map(|t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// Print one issue in full, with its comments
+pub fn display_issue_details(details: &IssueDetails, colors: &Colors) -> Result<(), anyhow::Error> {
+    let issue = &details.issue;
+    let tags = issue
+        .tags
+        .iter()
+        .map(|t| t.name.clone())
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    println!(
+        "{} {}",
+        issue.id.color(colors.issue_id()),
+        issue.title.color(colors.title())
+    );
+    println!("{:-<40}", "-"); // Divider line
+    println!("State:     {}", issue.state);
+    println!("Author:    {}", issue.author.as_deref().unwrap_or_default());
+    println!("Assignee:  {}", issue.assignees.join(", "));
+    println!("Tags:      {}", tags.color(colors.tags()));
+    if let Some(milestone) = &issue.milestone {
+        println!("Milestone: {}", milestone);
+    }
+    if let Some(due) = &issue.due {
+        println!("Due:       {}", due);
+    }
+    println!("Created:   {}", format_timestamp(&issue.created_at));
+    println!("Updated:   {}", format_timestamp(&issue.updated_at));
+    println!("URL:       {}", issue.html_url);
+
+    if let Some(body) = issue
+        .description
+        .as_deref()
+        .filter(|b| !b.trim().is_empty())
+    {
+        println!();
+        println!("{}", body.trim_end());
+    }
+
+    println!();
+    println!("Comments ({})", details.comments.len());
+    println!("{:-<40}", "-"); // Divider line
+    for comment in &details.comments {
+        println!(
+            "{} {}",
+            comment.author.color(colors.issue_id()),
+            format_timestamp(&comment.created_at)
+        );
+        for line in comment.body.trim_end().lines() {
+            println!("  {}", line);
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+/// The stores with these ids, or every store when there are none
+fn select_stores<'a>(
+    config: &'a AppConfig,
+    store_ids: &[String],
+) -> Result<Vec<&'a dyn IssueProvider>, TskmstrError> {
+    let stores: Vec<&dyn IssueProvider> = config
+        .issue_stores()
+        .iter()
+        .map(|store| store.as_ref())
+        .filter(|s| store_ids.is_empty() || store_ids.contains(&s.store_id()))
+        .collect();
+
+    if let Some(id) = store_ids
+        .iter()
+        .find(|id| !stores.iter().any(|s| &s.store_id() == *id))
+    {
+        return Err(TskmstrError::UnknownStore {
+            id: id.clone(),
+            known: config.provider_ids(),
+        });
+    }
+
+    Ok(stores)
+}
+
+/// List the issues of the stores with these ids, or of every store when there are none
 pub async fn aggregate_and_display_all_tasks(
-    provider_id: &Option<String>,
+    store_ids: &[String],
     config: &AppConfig,
     colors: &Colors,
     all: &bool,
+    cache_policy: &CachePolicy,
+    format: &OutputFormat,
+    group_by: &GroupBy,
 ) -> Result<(), anyhow::Error> {
-    let mut all_issues = Vec::new();
+    let stores = select_stores(config, store_ids)?;
 
-    let github_tasks = collect_tasks_from_github(&config.github_com, provider_id).await?;
-    all_issues.extend(github_tasks);
+    let mut cache = IssueCache::load();
 
-    let gitlab_tasks = collect_tasks_from_gitlab(&config.gitlab_com, provider_id).await?;
-    all_issues.extend(gitlab_tasks);
+    // decide up front which stores can be served from the cache
+    let plan: Vec<(&dyn IssueProvider, bool)> = stores
+        .iter()
+        .copied()
+        .map(|store| {
+            let needs_fetch = match cache_policy {
+                CachePolicy::Refresh => true,
+                CachePolicy::MaxAge(max_age) => cache
+                    .get_fresh(&store.store_id(), &store.location(), *max_age)
+                    .is_none(),
+                CachePolicy::Offline => false,
+            };
+            (store, needs_fetch)
+        })
+        .collect();
 
-    let jira_tasks = collect_tasks_from_jira(&config.jira, provider_id).await?;
-    all_issues.extend(jira_tasks);
+    let timeout = Duration::from_secs(config.fetch.timeout_secs);
 
-    display_tasks_in_table(&all_issues, colors, &config.labels.priority_labels, all)
+    // fetch the stores concurrently, but keep their results in config order
+    let results: Vec<_> = stream::iter(plan)
+        .map(|(store, needs_fetch)| async move {
+            let fetched = match needs_fetch {
+                true => Some(tokio::time::timeout(timeout, store.list_issues()).await),
+                false => None,
+            };
+            (store, fetched)
+        })
+        .buffered(config.fetch.concurrency.max(1))
+        .collect()
+        .await;
 
+    let mut all_issues = Vec::new();
+    let mut cache_updated = false;
+    let mut failures: Vec<anyhow::Error> = Vec::new();
+    for (store, fetched) in results {
+        let (store_id, location) = (store.store_id(), store.location());
+        match fetched {
+            Some(Ok(Ok(issues))) => {
+                cache.update(&store_id, &location, &issues);
+                cache_updated = true;
+                all_issues.extend(issues);
+            }
+            Some(failed) => {
+                let error = match failed {
+                    Ok(Err(e)) => e,
+                    _ => anyhow!("Timed out after {}s", config.fetch.timeout_secs),
+                };
+                let error = error.context(format!("issue store {}", store_id));
+                failures.push(error);
+
+                if let Some(cached) = cache.get(&store_id, &location) {
+                    eprintln!(
+                        "Showing the cached issues for {}, fetched {} ago",
+                        store_id,
+                        describe_age(cached.age())
+                    );
+                    all_issues.extend(cached.issues.clone());
+                }
+            }
+            None => match cache.get(&store_id, &location) {
+                Some(cached) => {
+                    debug!(
+                        "{} served from cache, fetched {} ago",
+                        store_id,
+                        describe_age(cached.age())
+                    );
+                    all_issues.extend(cached.issues.clone());
+                }
+                None => eprintln!(
+                    "Warning: there are no cached issues for {} ({}), list once while online",
+                    store_id, location
+                ),
+            },
+        }
+    }
+
+    if cache_updated {
+        if let Err(e) = cache.save() {
+            warn!("unable to write the issue cache: {}", e);
+        }
+    }
+
+    match format {
+        OutputFormat::Text => match group_by {
+            GroupBy::Labels => {
+                let priority = PriorityRules::from_config(&config.labels)?;
+                display_tasks_in_table(&all_issues, colors, &priority, all)?
+            }
+            GroupBy::Status => display_tasks_by_status(&all_issues, colors, all)?,
+        },
+        _ => {
+            let records: Vec<IssueRecord> = all_issues.iter().map(IssueRecord::from).collect();
+            print_records(&records, format)?
+        }
+    }
+
+    // the issues that could be fetched are shown, but the listing still fails
+    report_failures(failures)
 }
 
-pub async fn list_issue_stores(config: &AppConfig) -> Result<(), anyhow::Error> {
-    
-    for g in &config.github_com {
-        for x in &g.repositories {
-            println!("{} - {}/{}/{}", x.id, g.endpoint, x.owner, x.repo);
+/// The most recently closed issues across the stores, to find one to `reopen`
+pub async fn display_closed_tasks(
+    store_ids: &[String],
+    config: &AppConfig,
+    limit: usize,
+    all: &bool,
+    format: &OutputFormat,
+) -> Result<(), anyhow::Error> {
+    let stores = select_stores(config, store_ids)?;
+    let timeout = Duration::from_secs(config.fetch.timeout_secs);
+
+    let results: Vec<_> = stream::iter(stores.iter())
+        .map(|store| async move {
+            let fetched = tokio::time::timeout(timeout, store.list_closed_issues(limit)).await;
+            (store, fetched)
+        })
+        .buffered(config.fetch.concurrency.max(1))
+        .collect()
+        .await;
+
+    let mut closed = Vec::new();
+    let mut failures: Vec<anyhow::Error> = Vec::new();
+    for (store, fetched) in results {
+        match fetched {
+            Ok(Ok(issues)) => closed.extend(issues),
+            Ok(Err(e)) => failures.push(e.context(format!("issue store {}", store.store_id()))),
+            Err(_) => failures.push(anyhow!(
+                "issue store {}: Timed out after {}s",
+                store.store_id(),
+                config.fetch.timeout_secs
+            )),
         }
     }
 
-    for g in &config.gitlab_com {
-        for x in &g.repositories {
-            println!("{} - {}/{}", x.id, g.endpoint, x.project_id);
+    // newest first, across every store
+    closed.sort_by_key(|issue| std::cmp::Reverse(issue.closed_at.or(issue.updated_at)));
+    closed.truncate(limit);
+
+    match format {
+        OutputFormat::Text => {
+            let colors = &config.colors;
+            for issue in &closed {
+                let closed_at = issue
+                    .closed_at
+                    .map(|t| format!(" [closed {}]", t.with_timezone(&Local).date_naive()))
+                    .unwrap_or_default();
+                let details = match all {
+                    false => "".to_string(),
+                    true => format!(" - {}", issue.html_url),
+                };
+                println!(
+                    " - {} {}{}{}",
+                    issue.id.color(colors.issue_id()),
+                    issue.title.color(colors.title()),
+                    closed_at,
+                    details
+                );
+            }
+        }
+        _ => {
+            let records: Vec<IssueRecord> = closed.iter().map(IssueRecord::from).collect();
+            print_records(&records, format)?
         }
     }
 
-    for g in &config.jira {
-        for x in &g.projects {
-            println!("{} - {}/{}", x.id, g.endpoint, x.id);
+    report_failures(failures)
+}
+
+/// Print every failed store but the first, which is returned so the command fails
+fn report_failures(failures: Vec<anyhow::Error>) -> Result<(), anyhow::Error> {
+    let mut failures = failures.into_iter();
+    match failures.next() {
+        None => Ok(()),
+        Some(first) => {
+            for failure in failures {
+                eprintln!("Error: {:#}", failure);
+            }
+            Err(first)
         }
     }
+}
+
+pub async fn list_issue_stores(
+    config: &AppConfig,
+    format: &OutputFormat,
+) -> Result<(), anyhow::Error> {
+    if *format != OutputFormat::Text {
+        let records: Vec<IssueStoreRecord> = config
+            .issue_stores()
+            .iter()
+            .map(|store| IssueStoreRecord {
+                id: store.store_id(),
+                provider: store.provider().to_string(),
+                location: store.location(),
+            })
+            .collect();
+        return print_records(&records, format);
+    }
+
+    for store in config.issue_stores() {
+        println!("{} - {}", store.store_id(), store.location());
+    }
 
     Ok(())
 }
@@ -0,0 +1,81 @@
+use std::io::{self, BufRead, Write};
+
+use anyhow::{anyhow, Result};
+
+/// Ask a question, an empty answer is the default
+pub fn ask(question: &str, default: &str) -> Result<String> {
+    match default {
+        "" => print!("{}: ", question),
+        _ => print!("{} [{}]: ", question, default),
+    }
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer)? == 0 {
+        return Err(anyhow!("no answer, the input was closed"));
+    }
+    match answer.trim() {
+        "" => Ok(default.to_string()),
+        a => Ok(a.to_string()),
+    }
+}
+
+/// Ask until the answer is not empty
+pub fn ask_required(question: &str, default: &str) -> Result<String> {
+    loop {
+        let answer = ask(question, default)?;
+        if !answer.is_empty() {
+            return Ok(answer);
+        }
+    }
+}
+
+pub fn confirm(question: &str, default: bool) -> Result<bool> {
+    let answer = ask(question, if default { "Y/n" } else { "y/N" })?;
+    Ok(match answer.to_lowercase().as_str() {
+        "y" | "yes" => true,
+        "n" | "no" => false,
+        _ => default,
+    })
+}
+
+/// Pick some of a list, by their numbers, e.g. `1,3`
+pub fn choose(items: &[String]) -> Result<Vec<String>> {
+    for (n, item) in items.iter().enumerate() {
+        println!("  {:>3}. {}", n + 1, item);
+    }
+    loop {
+        let answer = ask("Which ones (e.g. 1,3), blank for none", "")?;
+        let picked: Result<Vec<String>, _> = answer
+            .split(',')
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .map(|n| {
+                n.parse::<usize>()
+                    .ok()
+                    .and_then(|n| n.checked_sub(1))
+                    .and_then(|n| items.get(n))
+                    .cloned()
+                    .ok_or(n)
+            })
+            .collect();
+        match picked {
+            Ok(picked) => return Ok(picked),
+            Err(n) => println!("'{}' is not one of the numbers", n),
+        }
+    }
+}
+
+/// Pick one of a list by its number
+pub fn choose_one(question: &str, items: &[String]) -> Result<usize> {
+    for (n, item) in items.iter().enumerate() {
+        println!("  {:>3}. {}", n + 1, item);
+    }
+    loop {
+        let answer = ask_required(question, "")?;
+        match answer.parse::<usize>() {
+            Ok(n) if (1..=items.len()).contains(&n) => return Ok(n - 1),
+            _ => println!("'{}' is not one of the numbers", answer),
+        }
+    }
+}
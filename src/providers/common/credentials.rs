@@ -1,35 +1,343 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
 use keyring::Entry;
+use lazy_static::lazy_static;
+use log::debug;
 use serde::Deserialize;
+use tokio::sync::OnceCell;
+
+use crate::errors::TskmstrError;
+
+/// Where the API token of a provider comes from. Any of the sources can be
+/// set; they are tried in the order env, file, command, keyring and the
+/// first one that has a token is used.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Credential {
+    /// the login (Jira's e-mail), and the user of the keyring entry
+    pub username: Option<String>,
+
+    /// an environment variable holding the token, e.g. GITHUB_TOKEN
+    pub env: Option<String>,
+
+    /// a file holding just the token
+    pub file: Option<String>,
+
+    /// a command that prints the token, e.g. `pass show github.com/token`
+    pub command: Option<String>,
+
+    /// the service of the OS keyring entry (with `username`)
+    pub service: Option<String>,
+}
+
+/// One way of reading a token
+#[derive(Debug, Clone, PartialEq)]
+pub enum CredentialSource {
+    Env(String),
+    File(String),
+    Command(String),
+    Keyring { service: String, username: String },
+}
+
+impl Credential {
+    /// the configured sources, in the order they are tried
+    pub fn sources(&self) -> Vec<CredentialSource> {
+        let mut sources = Vec::new();
+        if let Some(var) = &self.env {
+            sources.push(CredentialSource::Env(var.clone()));
+        }
+        if let Some(file) = &self.file {
+            sources.push(CredentialSource::File(file.clone()));
+        }
+        if let Some(command) = &self.command {
+            sources.push(CredentialSource::Command(command.clone()));
+        }
+        if let (Some(service), Some(username)) = (&self.service, &self.username) {
+            sources.push(CredentialSource::Keyring {
+                service: service.clone(),
+                username: username.clone(),
+            });
+        }
+        sources
+    }
+
+    /// Save a token where it will be read from: the file, otherwise the keyring.
+    /// Returns where it was saved
+    pub fn store_token(&self, token: &str) -> Result<CredentialSource, String> {
+        for source in self.sources() {
+            match &source {
+                CredentialSource::File(file) => {
+                    let path = shellexpand::tilde(file).to_string();
+                    write_token_file(Path::new(&path), token)
+                        .map_err(|e| format!("{}: {}", path, e))?
+                }
+                CredentialSource::Keyring { service, username } => Entry::new(service, username)
+                    .and_then(|entry| entry.set_password(token))
+                    .map_err(|e| format!("unable to write the {}: {}", source, e))?,
+                // env and command are only ever read
+                _ => continue,
+            }
+
+            TOKENS
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(self);
+            return Ok(source);
+        }
+
+        Err("the credential has no file, or service and username, to store a token in".to_string())
+    }
+}
+
+/// only readable by the user, on unix; the file is never readable by others, even briefly
+fn write_token_file(path: &Path, token: &str) -> std::io::Result<()> {
+    let mut dir_builder = std::fs::DirBuilder::new();
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt, PermissionsExt};
+        dir_builder.mode(0o700);
+        options.mode(0o600);
+        // an existing file keeps its mode when opened
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        dir_builder.recursive(true).create(dir)?;
+    }
+    options.open(path)?.write_all(token.as_bytes())
+}
+
+impl CredentialSource {
+    pub fn read_token(&self) -> Result<String, String> {
+        let token = match self {
+            CredentialSource::Env(var) => {
+                std::env::var(var).map_err(|_| format!("${} is not set", var))?
+            }
+            CredentialSource::File(file) => {
+                let path = shellexpand::tilde(file).to_string();
+                std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path, e))?
+            }
+            CredentialSource::Command(command) => run_token_command(command)?,
+            CredentialSource::Keyring { service, username } => Entry::new(service, username)
+                .and_then(|entry| entry.get_password())
+                .map_err(|e| {
+                    format!(
+                        "no keyring entry for service '{}', user '{}' ({})",
+                        service, username, e
+                    )
+                })?,
+        };
+
+        match token.trim() {
+            "" => Err(format!("{} is empty", self)),
+            t => Ok(t.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for CredentialSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CredentialSource::Env(var) => write!(f, "env ${}", var),
+            CredentialSource::File(file) => write!(f, "file {}", file),
+            CredentialSource::Command(command) => write!(f, "command `{}`", command),
+            CredentialSource::Keyring { service, username } => {
+                write!(f, "keyring {}/{}", service, username)
+            }
+        }
+    }
+}
+
+fn run_token_command(command: &str) -> Result<String, String> {
+    debug!("running the credential command `{}`", command);
+
+    let output = if cfg!(windows) {
+        Command::new("cmd").args(["/C", command]).output()
+    } else {
+        Command::new("sh").args(["-c", command]).output()
+    }
+    .map_err(|e| format!("`{}` could not be run: {}", command, e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "`{}` failed ({}): {}",
+            command,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    String::from_utf8(output.stdout).map_err(|_| format!("`{}` did not print text", command))
+}
 
-#[derive(Debug, Deserialize, Clone)]
-/// application, service and username of an entry in the OS keyring
-pub struct CredentialKeyringEntry {
-    pub service: String,
-    pub username: String,
+lazy_static! {
+    /// tokens already read this run, so a command or keyring prompt happens once;
+    /// stores sharing a credential wait on its cell, not on every other credential
+    static ref TOKENS: Mutex<HashMap<Credential, Arc<OnceCell<String>>>> =
+        Mutex::new(HashMap::new());
 }
-pub trait HasSecretToken {
+
+/// The token of the first source that has one, or why none of them did
+fn read_first_token(provider_id: &str, sources: Vec<CredentialSource>) -> Result<String, String> {
+    let mut failures = Vec::new();
+    for source in sources {
+        match source.read_token() {
+            Ok(token) => {
+                debug!("{}: using the token from {}", provider_id, source);
+                return Ok(token);
+            }
+            Err(reason) => failures.push(reason),
+        }
+    }
+    Err(failures.join("; "))
+}
+
+#[async_trait]
+pub trait HasSecretToken: Sync {
     fn task_provider_id(&self) -> String;
 
-    fn credential(&self) -> Option<CredentialKeyringEntry>;
+    fn credential(&self) -> Option<Credential>;
 
-    fn get_username(&self) -> String {
-        self.credential().unwrap().username.clone()
+    fn get_username(&self) -> Result<String, TskmstrError> {
+        self.credential().and_then(|c| c.username).ok_or_else(|| {
+            self.missing_credential("there is no `credential.username` in the config")
+        })
     }
 
-    fn get_token(&self) -> String {
-        
+    async fn get_token(&self) -> Result<String, TskmstrError> {
+        let credential = self
+            .credential()
+            .ok_or_else(|| self.missing_credential("there is no `credential:` in the config"))?;
 
-        match self.credential() {
-            Some(cke) => Entry::new(&cke.service, &cke.username)
-                .unwrap_or_else(|_| panic!("failed to get the keyring for {}/{}",
-                        &cke.service, &cke.username))
-                .get_password()
-                .unwrap_or_else(|_| panic!("failed to get the API token for {}/{}",
-                        &cke.service, &cke.username)),
-            _ => panic!(
-                "Please provide a credentials in config for: {}",
-                self.task_provider_id()
-            ),
+        let sources = credential.sources();
+        if sources.is_empty() {
+            return Err(self.missing_credential(
+                "the credential has none of env, file, command or service (with username)",
+            ));
         }
+
+        let cell = TOKENS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(credential)
+            .or_default()
+            .clone();
+
+        // a command or the keyring can block (or prompt), so not on a runtime thread
+        let provider_id = self.task_provider_id();
+        cell.get_or_try_init(|| async move {
+            tokio::task::spawn_blocking(move || read_first_token(&provider_id, sources))
+                .await
+                .map_err(|e| format!("the token could not be read: {}", e))?
+        })
+        .await
+        .cloned()
+        .map_err(|reason| self.missing_credential(&reason))
+    }
+
+    fn missing_credential(&self, reason: &str) -> TskmstrError {
+        TskmstrError::MissingCredential {
+            provider_id: self.task_provider_id(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token_file(token: &str) -> (tempfile::TempDir, String) {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("token");
+        std::fs::write(&file, token).unwrap();
+        (dir, file.display().to_string())
+    }
+
+    #[test]
+    fn sources_are_tried_env_file_command_keyring() {
+        let credential = Credential {
+            username: Some("ann".to_string()),
+            env: Some("GITHUB_TOKEN".to_string()),
+            file: Some("~/.tskmstr/token".to_string()),
+            command: Some("pass show github".to_string()),
+            service: Some("tskmstr".to_string()),
+        };
+
+        assert_eq!(
+            credential.sources(),
+            [
+                CredentialSource::Env("GITHUB_TOKEN".to_string()),
+                CredentialSource::File("~/.tskmstr/token".to_string()),
+                CredentialSource::Command("pass show github".to_string()),
+                CredentialSource::Keyring {
+                    service: "tskmstr".to_string(),
+                    username: "ann".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn the_keyring_needs_a_username() {
+        let credential = Credential {
+            service: Some("tskmstr".to_string()),
+            ..Default::default()
+        };
+        assert!(credential.sources().is_empty());
+    }
+
+    #[test]
+    fn the_env_token_comes_first() {
+        let (_dir, file) = token_file("from-file");
+        std::env::set_var("TSKMSTR_TEST_ENV_FIRST", "from-env");
+
+        let sources = vec![
+            CredentialSource::Env("TSKMSTR_TEST_ENV_FIRST".to_string()),
+            CredentialSource::File(file),
+        ];
+        assert_eq!(read_first_token("gh", sources).unwrap(), "from-env");
+    }
+
+    #[test]
+    fn an_unset_or_empty_source_falls_back_to_the_next() {
+        let (_dir, file) = token_file("  from-file\n");
+        std::env::set_var("TSKMSTR_TEST_EMPTY", " ");
+
+        let sources = vec![
+            CredentialSource::Env("TSKMSTR_TEST_UNSET".to_string()),
+            CredentialSource::Env("TSKMSTR_TEST_EMPTY".to_string()),
+            CredentialSource::File(file),
+            CredentialSource::Command("echo from-command".to_string()),
+        ];
+        assert_eq!(read_first_token("gh", sources).unwrap(), "from-file");
+    }
+
+    #[test]
+    fn a_missing_file_falls_back_to_the_command() {
+        let sources = vec![
+            CredentialSource::File("/nonexistent/tskmstr/token".to_string()),
+            CredentialSource::Command("echo from-command".to_string()),
+        ];
+        assert_eq!(read_first_token("gh", sources).unwrap(), "from-command");
+    }
+
+    #[test]
+    fn every_failure_is_reported() {
+        let sources = vec![
+            CredentialSource::Env("TSKMSTR_TEST_UNSET".to_string()),
+            CredentialSource::Command("exit 3".to_string()),
+        ];
+        let reason = read_first_token("gh", sources).unwrap_err();
+        assert!(reason.starts_with("$TSKMSTR_TEST_UNSET is not set; `exit 3` failed"));
     }
 }
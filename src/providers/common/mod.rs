@@ -1,2 +1,4 @@
 pub mod credentials;
 pub mod model;
+pub mod provider;
+pub mod status;
@@ -1,6 +1,7 @@
-use serde::Deserialize;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Issue {
     /// The title of the issue
     pub title: String,
@@ -11,12 +12,240 @@ pub struct Issue {
     /// task/ issue id referencing the foreign system
     pub id: String,
 
+    /// the issue store (`id:` in config) this issue belongs to
+    #[serde(default)]
+    pub store_id: String,
+
+    /// which provider the issue came from: github, gitlab or jira
+    #[serde(default)]
+    pub provider: String,
+
     /// List of labels, or tags
     #[serde(rename = "labels")]
     pub tags: Vec<Label>,
+
+    /// open/opened/closed, or the Jira workflow status
+    #[serde(default)]
+    pub state: String,
+
+    #[serde(default)]
+    pub assignees: Vec<String>,
+
+    /// who opened the issue (the reporter, for Jira)
+    #[serde(default)]
+    pub author: Option<String>,
+
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+
+    /// when the issue was closed (resolved, for Jira), if it is
+    #[serde(default)]
+    pub closed_at: Option<DateTime<Utc>>,
+
+    /// todo, doing or done, from the store's `status:` mapping
+    #[serde(default)]
+    pub status: Option<WorkStatus>,
+
+    /// GitHub milestone due_on, GitLab due_date or Jira duedate
+    #[serde(default)]
+    pub due: Option<NaiveDate>,
+
+    /// The milestone (GitHub, GitLab) or sprint (Jira)
+    #[serde(default)]
+    pub milestone: Option<String>,
+
+    /// The body/description, as plain text or markdown
+    #[serde(default)]
+    pub description: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Label {
     pub name: String,
 }
+
+/// An issue with the comments `show` displays alongside it
+#[derive(Debug, Clone)]
+pub struct IssueDetails {
+    pub issue: Issue,
+
+    /// oldest first
+    pub comments: Vec<Comment>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Comment {
+    pub author: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub body: String,
+}
+
+/// Where an issue is in the work, the same for every provider
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkStatus {
+    Todo,
+    Doing,
+    Done,
+}
+
+impl WorkStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkStatus::Todo => "todo",
+            WorkStatus::Doing => "doing",
+            WorkStatus::Done => "done",
+        }
+    }
+}
+
+impl std::fmt::Display for WorkStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Why an issue was closed, GitHub's `state_reason`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CloseReason {
+    Completed,
+    #[value(name = "not_planned", alias = "not-planned")]
+    NotPlanned,
+    Duplicate,
+}
+
+impl CloseReason {
+    /// the GitHub `state_reason`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CloseReason::Completed => "completed",
+            CloseReason::NotPlanned => "not_planned",
+            CloseReason::Duplicate => "duplicate",
+        }
+    }
+}
+
+impl std::fmt::Display for CloseReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CloseReason::Completed => write!(f, "completed"),
+            CloseReason::NotPlanned => write!(f, "not planned"),
+            CloseReason::Duplicate => write!(f, "a duplicate"),
+        }
+    }
+}
+
+/// How an issue is closed: why, and what to say about it
+#[derive(Debug, Clone, Default)]
+pub struct Closure {
+    pub reason: Option<CloseReason>,
+    pub comment: Option<String>,
+}
+
+impl Closure {
+    /// The note for a provider that has no close reason of its own (GitLab),
+    /// the reason and the comment
+    pub fn note(&self) -> Option<String> {
+        match (&self.reason, &self.comment) {
+            (None, None) => None,
+            (Some(reason), None) => Some(format!("Closed as {}.", reason)),
+            (None, Some(comment)) => Some(comment.clone()),
+            (Some(reason), Some(comment)) => Some(format!("Closed as {}.\n\n{}", reason, comment)),
+        }
+    }
+}
+
+/// The changes to an issue's content; only the fields that are set are sent
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IssueEdit {
+    pub title: Option<String>,
+    pub body: Option<String>,
+}
+
+impl IssueEdit {
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.body.is_none()
+    }
+
+    /// The field names changed, for the messages
+    pub fn describe(&self) -> String {
+        match (&self.title, &self.body) {
+            (Some(_), Some(_)) => "title and body",
+            (Some(_), None) => "title",
+            _ => "body",
+        }
+        .to_string()
+    }
+}
+
+/// Who an issue is assigned to: a user by name (a GitHub login, a GitLab username,
+/// a Jira e-mail or display name), or whoever the store's token authenticates as
+#[derive(Debug, Clone, PartialEq)]
+pub enum Assignee {
+    Me,
+    User(String),
+}
+
+/// `@me`, as the GitHub CLI has it, is the user of the token
+impl std::str::FromStr for Assignee {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "@me" => Ok(Assignee::Me),
+            name => Ok(Assignee::User(name.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for Assignee {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Assignee::Me => write!(f, "you"),
+            Assignee::User(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Who a provider's token authenticates as, for `auth status`
+#[derive(Debug, Clone)]
+pub struct AuthIdentity {
+    pub user: String,
+
+    /// the token's scopes, where the provider tells us
+    pub scopes: Option<Vec<String>>,
+
+    /// when the token expires, where the provider tells us
+    pub expires: Option<String>,
+}
+
+/// Read the timestamps the providers send; RFC 3339 (GitHub, GitLab)
+/// or Jira's `2023-10-02T14:11:28.123+1100`
+pub fn parse_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .or_else(|_| DateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f%z"))
+        .map(|t| t.with_timezone(&Utc))
+        .ok()
+}
+
+/// Read a due date, either a plain `2023-10-02` or a full timestamp
+pub fn parse_due_date(s: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .ok()
+        .or_else(|| parse_timestamp(s).map(|t| t.date_naive()))
+}
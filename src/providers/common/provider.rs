@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+
+use crate::config::IssueTaskRepository;
+use crate::git_remote::GitRemote;
+use crate::providers::common::model::{
+    Assignee, Closure, Issue, IssueDetails, IssueEdit, WorkStatus,
+};
+
+/// One configured issue store (a GitHub repository, a GitLab project, a Jira project)
+/// together with the provider configuration needed to reach it.
+///
+/// Everything `control.rs` and `output.rs` do to a store goes through this trait,
+/// so a new provider only needs to implement it (and be returned from `AppConfig::issue_stores`).
+#[async_trait]
+pub trait IssueProvider: Send + Sync {
+    /// The configured repository/project entry for this store
+    fn repository(&self) -> &dyn IssueTaskRepository;
+
+    /// Which provider this store belongs to: github, gitlab or jira
+    fn provider(&self) -> &'static str;
+
+    /// Where the issues live, used when listing the issue stores
+    fn location(&self) -> String;
+
+    /// The unique issue store id (`id:` in the config)
+    fn store_id(&self) -> String {
+        self.repository().id()
+    }
+
+    /// Whether this store holds the issues of a git remote's repository
+    fn matches_remote(&self, _remote: &GitRemote) -> bool {
+        false
+    }
+
+    /// List the open issues of this store (after applying any configured filter)
+    async fn list_issues(&self) -> Result<Vec<Issue>, anyhow::Error>;
+
+    /// Fetch a single issue
+    async fn get_issue(&self, issue_id: &str) -> Result<Issue, anyhow::Error>;
+
+    /// Fetch a single issue with its body, state, assignees and comments
+    async fn get_issue_details(&self, issue_id: &str) -> Result<IssueDetails, anyhow::Error>;
+
+    /// Create a new issue, returning what the provider created
+    async fn create_issue(
+        &self,
+        title: &str,
+        details: &str,
+        tags: &Option<Vec<String>>,
+        assignee: Option<&Assignee>,
+    ) -> Result<Issue, anyhow::Error>;
+
+    async fn close_issue(&self, issue_id: &str, closure: &Closure) -> Result<(), anyhow::Error>;
+
+    async fn reopen_issue(&self, issue_id: &str) -> Result<(), anyhow::Error>;
+
+    /// Move an issue through the workflow, to the status (or along the transition)
+    /// with this name; without one, show where it can move to
+    async fn move_issue(&self, _issue_id: &str, _to: Option<&str>) -> Result<(), anyhow::Error> {
+        Err(anyhow!(
+            "{} issues have no workflow to move through, only close and reopen",
+            self.provider()
+        ))
+    }
+
+    /// The text of an issue template kept in the repository, by name
+    async fn read_repo_template(&self, _name: &str) -> Result<String, anyhow::Error> {
+        Err(anyhow!(
+            "{} has no issue templates in a repository, use the template's text: or file:",
+            self.provider()
+        ))
+    }
+
+    /// Move an issue to todo, doing or done, as the store's `status:` maps them
+    async fn set_status(&self, issue_id: &str, status: WorkStatus) -> Result<(), anyhow::Error>;
+
+    /// Up to `limit` of the most recently closed issues, newest first
+    async fn list_closed_issues(&self, limit: usize) -> Result<Vec<Issue>, anyhow::Error>;
+
+    /// Change the title and/or body of an issue
+    async fn edit_issue(&self, issue_id: &str, edit: &IssueEdit) -> Result<(), anyhow::Error>;
+
+    /// Add an assignee to an issue (a Jira issue has only the one, so it is replaced)
+    async fn assign_issue(&self, issue_id: &str, assignee: &Assignee) -> Result<(), anyhow::Error>;
+
+    /// Remove an assignee from an issue, or everyone assigned to it when there is none
+    async fn unassign_issue(
+        &self,
+        issue_id: &str,
+        assignee: Option<&Assignee>,
+    ) -> Result<(), anyhow::Error>;
+
+    async fn add_comment(&self, issue_id: &str, comment: &str) -> Result<(), anyhow::Error>;
+
+    async fn add_labels(
+        &self,
+        issue_id: &str,
+        labels: &HashSet<String>,
+    ) -> Result<(), anyhow::Error>;
+
+    async fn remove_labels(
+        &self,
+        issue_id: &str,
+        labels: &HashSet<String>,
+    ) -> Result<(), anyhow::Error>;
+}
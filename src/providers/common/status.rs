@@ -0,0 +1,54 @@
+use anyhow::{anyhow, Result};
+use log::debug;
+use std::collections::HashSet;
+
+use crate::config::StatusMapping;
+use crate::providers::common::model::{Closure, WorkStatus};
+use crate::providers::common::provider::IssueProvider;
+
+/// Set the status of an issue that is only open or closed (GitHub, GitLab):
+/// swap its status label, and close it for done or reopen it for todo and doing
+pub async fn set_status_by_labels(
+    store: &dyn IssueProvider,
+    mapping: &StatusMapping,
+    issue_id: &str,
+    status: WorkStatus,
+) -> Result<()> {
+    let label = mapping.name_for(status);
+    if status == WorkStatus::Doing && label.is_none() {
+        return Err(anyhow!(
+            "{} has no label for doing, set status.doing (e.g. status::doing) on it in the config",
+            store.store_id()
+        ));
+    }
+
+    let issue = store.get_issue(issue_id).await?;
+    let has = |name: &str| issue.tags.iter().any(|t| t.name.eq_ignore_ascii_case(name));
+
+    // only the labels the issue has, removing one it doesn't is an error
+    let stale: HashSet<String> = [WorkStatus::Todo, WorkStatus::Doing, WorkStatus::Done]
+        .into_iter()
+        .filter(|other| *other != status)
+        .filter_map(|other| mapping.name_for(other))
+        .filter(|name| has(name))
+        .map(|name| name.to_string())
+        .collect();
+    if !stale.is_empty() {
+        store.remove_labels(issue_id, &stale).await?;
+    }
+    if let Some(label) = label.filter(|label| !has(label)) {
+        store
+            .add_labels(issue_id, &HashSet::from([label.to_string()]))
+            .await?;
+    }
+
+    let closed = issue.state == "closed";
+    match status {
+        WorkStatus::Done if !closed => store.close_issue(issue_id, &Closure::default()).await?,
+        WorkStatus::Todo | WorkStatus::Doing if closed => store.reopen_issue(issue_id).await?,
+        _ => debug!("{} is already {}", issue.id, issue.state),
+    }
+
+    println!("{} is now {}.", issue.id, status);
+    Ok(())
+}
@@ -2,14 +2,28 @@ use log::debug;
 use std::collections::HashSet;
 
 use reqwest::{
-    header::{HeaderMap, ACCEPT, AUTHORIZATION, USER_AGENT},
+    header::{HeaderMap, ACCEPT, AUTHORIZATION, LINK, USER_AGENT},
     Client,
 };
 
-use crate::providers::common::{credentials::HasSecretToken, model::Issue};
+use crate::errors::http_error;
+use crate::providers::common::{
+    credentials::HasSecretToken,
+    model::{
+        parse_due_date, parse_timestamp, Assignee, AuthIdentity, Closure, Comment, Issue,
+        IssueDetails, IssueEdit,
+    },
+};
 use crate::providers::github::model::GitHubConfig;
-use crate::providers::{common::model::Label, github::model::GitHubIssue};
+use crate::providers::{
+    common::model::Label,
+    github::model::{
+        GitHubComment, GitHubContent, GitHubIssue, GitHubRepositorySummary, GitHubUser,
+    },
+};
 
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose, Engine as _};
 use serde_json::json;
 
 use super::model::{GitHubRepository, NewComment};
@@ -23,117 +37,371 @@ pub fn construct_github_header(token: &str) -> HeaderMap {
 }
 
 pub async fn close_task_github(
+    client: &Client,
     github_config: &GitHubConfig,
     repo_config: &GitHubRepository,
-    issue_id: &String,
+    issue_id: &str,
+    closure: &Closure,
 ) -> Result<(), anyhow::Error> {
-    let client = Client::new();
+    // the comment goes first, so it sits above the close in the timeline
+    if let Some(comment) = &closure.comment {
+        let new_comment = NewComment {
+            body: comment.clone(),
+        };
+        add_comment_to_github_issue(client, repo_config, github_config, issue_id, new_comment)
+            .await?;
+    }
 
     let url = format!(
         "{}/repos/{}/{}/issues/{}",
-        github_config.endpoint, repo_config.owner, repo_config.repo, &issue_id
+        github_config.endpoint, repo_config.owner, repo_config.repo, issue_id
     );
 
     debug!("github: will close {}", url);
 
+    let mut payload = serde_json::json!({
+        "state": "closed"
+    });
+    if let Some(reason) = closure.reason {
+        payload["state_reason"] = reason.as_str().into();
+    }
+
+    let response = client
+        .patch(&url)
+        .headers(construct_github_header(&github_config.get_token().await?))
+        .json(&payload)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(http_error(
+            response,
+            format!(
+                "Unable to close task {} in GitHub repo {}/{}",
+                issue_id, repo_config.owner, repo_config.repo
+            ),
+        )
+        .await);
+    }
+
+    println!(
+        "Task {} closed in GitHub repo: {}/{}",
+        issue_id, repo_config.owner, repo_config.repo
+    );
+    Ok(())
+}
+
+pub async fn reopen_task_github(
+    client: &Client,
+    github_config: &GitHubConfig,
+    repo_config: &GitHubRepository,
+    issue_id: &str,
+) -> Result<(), anyhow::Error> {
+    let url = format!(
+        "{}/repos/{}/{}/issues/{}",
+        github_config.endpoint, repo_config.owner, repo_config.repo, issue_id
+    );
+
+    debug!("github: will reopen {}", url);
+
     let response = client
         .patch(&url)
-        .headers(construct_github_header(&github_config.get_token()))
+        .headers(construct_github_header(&github_config.get_token().await?))
         .json(&serde_json::json!({
-            "state": "closed"
+            "state": "open"
         }))
         .send()
         .await?;
 
-    if response.status().is_success() {
-        println!(
-            "Task {} closed in GitHub repo: {}/{}",
-            issue_id, repo_config.owner, repo_config.repo
-        );
-    } else {
-        println!(
-            "Error: Unable to close task {} in GitHub repo {}/{}. Status: {:?}",
-            issue_id,
-            repo_config.owner,
-            repo_config.repo,
-            response.status()
-        );
+    if !response.status().is_success() {
+        return Err(http_error(
+            response,
+            format!(
+                "Unable to reopen task {} in GitHub repo {}/{}",
+                issue_id, repo_config.owner, repo_config.repo
+            ),
+        )
+        .await);
     }
 
+    println!(
+        "Task {} reopened in GitHub repo: {}/{}",
+        issue_id, repo_config.owner, repo_config.repo
+    );
     Ok(())
 }
 
+/// The most recently closed issues, newest first (one page, after the configured filter)
+pub async fn collect_closed_tasks_from_github(
+    client: &Client,
+    github_config: &GitHubConfig,
+    repo: &GitHubRepository,
+    limit: usize,
+) -> Result<Vec<Issue>, anyhow::Error> {
+    let optional_filter = repo
+        .filter
+        .as_ref()
+        .map_or("".to_string(), |filt| format!("&{}", filt));
+
+    // GitHub can't sort by closed_at, but closing an issue updates it
+    let url = format!(
+        "{}/repos/{}/{}/issues?state=closed&sort=updated&direction=desc&per_page={}{}",
+        github_config.endpoint,
+        repo.owner,
+        repo.repo,
+        limit.clamp(1, 100),
+        optional_filter
+    );
+
+    debug!("github:get closed issues {}", url);
+
+    let response = client
+        .get(&url)
+        .headers(construct_github_header(&github_config.get_token().await?))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(http_error(
+            response,
+            format!(
+                "Unable to fetch closed issues for {}/{}",
+                repo.owner, repo.repo
+            ),
+        )
+        .await);
+    }
+
+    let github_issues: Vec<GitHubIssue> = response.json().await?;
+    Ok(github_issues
+        .into_iter()
+        .map(|github_issue| to_issue(repo, github_issue))
+        .collect())
+}
+
+fn to_issue(repo: &GitHubRepository, github_issue: GitHubIssue) -> Issue {
+    let status = repo.status.status_of_labels(
+        github_issue.labels.iter().map(|l| l.name.as_str()),
+        github_issue.state.as_deref() == Some("closed"),
+    );
+    Issue {
+        id: format!("{}/{}", repo.id, github_issue.number),
+        store_id: repo.id.clone(),
+        provider: "github".to_string(),
+        title: github_issue.title,
+        html_url: github_issue.html_url,
+        tags: github_issue
+            .labels
+            .into_iter()
+            .map(|l| Label { name: l.name })
+            .collect(),
+        state: github_issue.state.unwrap_or_default(),
+        assignees: github_issue
+            .assignees
+            .into_iter()
+            .map(|a| a.login)
+            .collect(),
+        author: github_issue.user.map(|u| u.login),
+        created_at: github_issue.created_at.as_deref().and_then(parse_timestamp),
+        updated_at: github_issue.updated_at.as_deref().and_then(parse_timestamp),
+        closed_at: github_issue.closed_at.as_deref().and_then(parse_timestamp),
+        status: Some(status),
+        due: github_issue
+            .milestone
+            .as_ref()
+            .and_then(|m| m.due_on.as_deref())
+            .and_then(parse_due_date),
+        milestone: github_issue.milestone.map(|m| m.title),
+        description: github_issue.body,
+    }
+}
+
+/// the `rel="next"` URL of a GitHub `Link` header, if there is another page
+fn next_page_link(headers: &HeaderMap) -> Option<String> {
+    let link = headers.get(LINK)?.to_str().ok()?;
+    // <https://..&page=2>; rel="next", <https://..&page=5>; rel="last"
+    // (the URLs can contain commas from the filter, so don't split on them)
+    let rel_next = link.find("rel=\"next\"")?;
+    let start = link[..rel_next].rfind('<')? + 1;
+    let end = start + link[start..].find('>')?;
+    Some(link[start..end].to_string())
+}
+
 pub async fn collect_tasks_from_github(
-    github_config: &Vec<GitHubConfig>,
-    issue_store_id: &Option<String>,
+    client: &Client,
+    github_config: &GitHubConfig,
+    repo: &GitHubRepository,
 ) -> Result<Vec<Issue>, anyhow::Error> {
-    let client = Client::new();
-    let mut all_issues = Vec::new(); // Create a vector to collect all issues
-
-    for g in github_config {
-        for (_idx, repo) in g
-            .repositories
-            .iter()
-            .filter(|&r| issue_store_id.is_none() || issue_store_id.as_deref().is_some_and(|p| r.id == p))
-            .enumerate()
-        {
-            let optional_filter = repo
-                .filter
-                .as_ref()
-                .map_or("".to_string(), |filt| format!("?{}", filt));
-
-            let url = format!(
-                "{}/repos/{}/{}/issues{}",
-                g.endpoint, repo.owner, repo.repo, optional_filter
-            );
-
-            debug!("github:get issues {}", url);
-
-            let response = client
-                .get(&url)
-                .headers(construct_github_header(&g.get_token()))
-                .send()
-                .await?;
-
-            if response.status().is_success() {
-                let body = response.text().await?;
-
-                let github_issues: Vec<GitHubIssue> = serde_json::from_str(&body)?;
-                let issues = github_issues.into_iter().map(|github_issue| Issue {
-                    id: format!("{}/{}", repo.id, github_issue.number),
-                    title: github_issue.title,
-                    html_url: github_issue.html_url,
-                    tags: github_issue
-                        .labels
-                        .into_iter()
-                        .map(|l| Label { name: l.name })
-                        .collect(),
-                });
-                all_issues.extend(issues); // Add the collected issues to the vector
-            } else {
-                println!(
-                    "Error: Unable to fetch issues for {}/{}. Status: {:?}. Error: {:?}",
-                    repo.owner,
-                    repo.repo,
-                    response.status(),
-                    response.text().await?,
-
-                );
-            }
+    let optional_filter = repo
+        .filter
+        .as_ref()
+        .map_or("".to_string(), |filt| format!("&{}", filt));
+
+    let mut next_url = Some(format!(
+        "{}/repos/{}/{}/issues?per_page={}{}",
+        github_config.endpoint,
+        repo.owner,
+        repo.repo,
+        repo.max_issues.clamp(1, 100),
+        optional_filter
+    ));
+
+    let mut issues = Vec::new();
+    let mut truncated = false;
+
+    // follow the Link headers until we run out of pages, or reach max_issues
+    while let Some(url) = next_url.take() {
+        if issues.len() >= repo.max_issues {
+            truncated = true;
+            break;
+        }
+
+        debug!("github:get issues {}", url);
+
+        let response = client
+            .get(&url)
+            .headers(construct_github_header(&github_config.get_token().await?))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(http_error(
+                response,
+                format!("Unable to fetch issues for {}/{}", repo.owner, repo.repo),
+            )
+            .await);
         }
+
+        next_url = next_page_link(response.headers());
+        let body = response.text().await?;
+
+        let github_issues: Vec<GitHubIssue> = serde_json::from_str(&body)?;
+        issues.extend(
+            github_issues
+                .into_iter()
+                .map(|github_issue| to_issue(repo, github_issue)),
+        );
+    }
+
+    if issues.len() > repo.max_issues {
+        truncated = true;
+        issues.truncate(repo.max_issues);
+    }
+
+    if truncated {
+        eprintln!(
+            "Warning: only the first {} issues of {} ({}/{}) are shown, raise max_issues to see more",
+            repo.max_issues, repo.id, repo.owner, repo.repo
+        );
+    }
+
+    Ok(issues)
+}
+
+async fn fetch_github_issue(
+    client: &Client,
+    github_config: &GitHubConfig,
+    repo: &GitHubRepository,
+    issue_number: &str,
+) -> Result<GitHubIssue, anyhow::Error> {
+    let url = format!(
+        "{}/repos/{}/{}/issues/{}",
+        github_config.endpoint, repo.owner, repo.repo, issue_number
+    );
+
+    debug!("github:get issue {}", url);
+
+    let response = client
+        .get(&url)
+        .headers(construct_github_header(&github_config.get_token().await?))
+        .send()
+        .await?;
+
+    if response.status().is_success() {
+        Ok(response.json::<GitHubIssue>().await?)
+    } else {
+        Err(http_error(
+            response,
+            format!(
+                "Unable to fetch issue {} from {}/{}",
+                issue_number, repo.owner, repo.repo
+            ),
+        )
+        .await)
     }
-    Ok(all_issues) // Return the vector of collected issues
+}
+
+pub async fn get_issue_github(
+    client: &Client,
+    github_config: &GitHubConfig,
+    repo: &GitHubRepository,
+    issue_number: &str,
+) -> Result<Issue, anyhow::Error> {
+    let github_issue = fetch_github_issue(client, github_config, repo, issue_number).await?;
+    Ok(to_issue(repo, github_issue))
+}
+
+pub async fn get_issue_details_github(
+    client: &Client,
+    github_config: &GitHubConfig,
+    repo: &GitHubRepository,
+    issue_number: &str,
+) -> Result<IssueDetails, anyhow::Error> {
+    let github_issue = fetch_github_issue(client, github_config, repo, issue_number).await?;
+
+    let mut next_url = Some(format!(
+        "{}/repos/{}/{}/issues/{}/comments?per_page=100",
+        github_config.endpoint, repo.owner, repo.repo, issue_number
+    ));
+    let mut comments: Vec<GitHubComment> = Vec::new();
+
+    // a long discussion is more than one page
+    while let Some(url) = next_url.take() {
+        debug!("github:get comments {}", url);
+
+        let response = client
+            .get(&url)
+            .headers(construct_github_header(&github_config.get_token().await?))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(http_error(
+                response,
+                format!(
+                    "Unable to fetch the comments of issue {} from {}/{}",
+                    issue_number, repo.owner, repo.repo
+                ),
+            )
+            .await);
+        }
+
+        next_url = next_page_link(response.headers());
+        comments.extend(response.json::<Vec<GitHubComment>>().await?);
+    }
+
+    Ok(IssueDetails {
+        issue: to_issue(repo, github_issue),
+        comments: comments
+            .into_iter()
+            .map(|c| Comment {
+                author: c.user.login,
+                created_at: parse_timestamp(&c.created_at),
+                body: c.body.unwrap_or_default(),
+            })
+            .collect(),
+    })
 }
 
 pub async fn add_new_task_github(
+    client: &Client,
     github_repo: &GitHubRepository,
     github_config: &GitHubConfig,
     title: &str,
     details: &str,
     tags: &Option<Vec<String>>,
-) -> Result<(), anyhow::Error> {
-    let client = Client::new();
-
+    assignee: Option<&Assignee>,
+) -> Result<Issue, anyhow::Error> {
     let add_url = format!(
         "{}/repos/{}/{}/issues",
         github_config.endpoint, github_repo.owner, github_repo.repo
@@ -152,40 +420,145 @@ pub async fn add_new_task_github(
         );
     }
 
+    if let Some(assignee) = assignee {
+        issue_details["assignees"] = json!([github_login(client, github_config, assignee).await?]);
+    }
+
     let response = client
         .post(&add_url)
-        .headers(construct_github_header(&github_config.get_token()))
+        .headers(construct_github_header(&github_config.get_token().await?))
         .header(reqwest::header::CONTENT_TYPE, "application/json")
         .body(issue_details.to_string())
         .send()
         .await?;
 
     if response.status().is_success() {
-        // debug!("{}",response.status());
-        // let t = response.text().await?;
         let issue: GitHubIssue = response.json::<GitHubIssue>().await?;
-        println!("New issue created:");
-        println!("Title: {}", issue.title);
-        println!("URL: {}", issue.html_url);
+        Ok(to_issue(github_repo, issue))
     } else {
-        println!(
-            "Error: Unable to create issue. Status: {:?}",
-            response.status()
-        );
+        Err(http_error(response, "Unable to create issue".to_string()).await)
+    }
+}
+
+/// The login of a user, `--me` being the user of the token
+async fn github_login(
+    client: &Client,
+    github_config: &GitHubConfig,
+    assignee: &Assignee,
+) -> Result<String, anyhow::Error> {
+    match assignee {
+        Assignee::Me => Ok(whoami_github(client, github_config).await?.user),
+        Assignee::User(login) => Ok(login.trim_start_matches('@').to_string()),
     }
+}
+
+pub async fn assign_github_issue(
+    client: &Client,
+    github_repo: &GitHubRepository,
+    github_config: &GitHubConfig,
+    issue_number: &str,
+    assignee: &Assignee,
+) -> Result<(), anyhow::Error> {
+    let login = github_login(client, github_config, assignee).await?;
+    let url = format!(
+        "{}/repos/{}/{}/issues/{}/assignees",
+        github_config.endpoint, github_repo.owner, github_repo.repo, issue_number
+    );
+
+    debug!("github:assign {} {}", url, login);
+
+    let response = client
+        .post(&url)
+        .headers(construct_github_header(&github_config.get_token().await?))
+        .json(&json!({ "assignees": [login] }))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(http_error(
+            response,
+            format!("Unable to assign issue {} to {}", issue_number, login),
+        )
+        .await);
+    }
+
+    // a user who can't be assigned (no access to the repository) is silently left out
+    let issue: GitHubIssue = response.json().await?;
+    if !issue
+        .assignees
+        .iter()
+        .any(|a| a.login.eq_ignore_ascii_case(&login))
+    {
+        return Err(anyhow!(
+            "{} can not be assigned issues in {}/{}",
+            login,
+            github_repo.owner,
+            github_repo.repo
+        ));
+    }
+
+    println!("Issue {} assigned to {}.", issue_number, login);
     Ok(())
 }
 
-use anyhow::{anyhow, Result};
+pub async fn unassign_github_issue(
+    client: &Client,
+    github_repo: &GitHubRepository,
+    github_config: &GitHubConfig,
+    issue_number: &str,
+    assignee: Option<&Assignee>,
+) -> Result<(), anyhow::Error> {
+    let logins: Vec<String> = match assignee {
+        Some(assignee) => vec![github_login(client, github_config, assignee).await?],
+        None => fetch_github_issue(client, github_config, github_repo, issue_number)
+            .await?
+            .assignees
+            .into_iter()
+            .map(|a| a.login)
+            .collect(),
+    };
+    if logins.is_empty() {
+        println!("Issue {} has no assignees.", issue_number);
+        return Ok(());
+    }
+
+    let url = format!(
+        "{}/repos/{}/{}/issues/{}/assignees",
+        github_config.endpoint, github_repo.owner, github_repo.repo, issue_number
+    );
+
+    debug!("github:unassign {} {:?}", url, logins);
+
+    let response = client
+        .delete(&url)
+        .headers(construct_github_header(&github_config.get_token().await?))
+        .json(&json!({ "assignees": logins }))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(http_error(
+            response,
+            format!("Unable to unassign issue {}", issue_number),
+        )
+        .await);
+    }
+
+    println!(
+        "Issue {} unassigned from {}.",
+        issue_number,
+        logins.join(", ")
+    );
+    Ok(())
+}
 
 pub async fn add_labels_to_github_issue(
+    client: &Client,
     github_repo: &GitHubRepository,
     github_config: &GitHubConfig,
-    issue_number: &String,
+    issue_number: &str,
     labels: &HashSet<String>,
 ) -> Result<(), anyhow::Error> {
-    let client = Client::new();
-
     // Create a URL for the GitHub API endpoint to add labels
     let url = format!(
         "{}/repos/{}/{}/issues/{}/labels",
@@ -199,7 +572,7 @@ pub async fn add_labels_to_github_issue(
     // Send a POST request to add labels
     let response = client
         .post(&url)
-        .headers(construct_github_header(&github_config.get_token()))
+        .headers(construct_github_header(&github_config.get_token().await?))
         .header(reqwest::header::ACCEPT, "application/vnd.github.v3+json")
         .json(&json_body)
         .send()
@@ -209,22 +582,17 @@ pub async fn add_labels_to_github_issue(
     if response.status().is_success() {
         Ok(())
     } else {
-        let error_msg = format!(
-            "Failed to add labels to GitHub issue: {:?}",
-            response.status()
-        );
-        Err(anyhow!(error_msg))
+        Err(http_error(response, "Failed to add labels to GitHub issue".to_string()).await)
     }
 }
 
 pub async fn remove_labels_from_github_issue(
+    client: &Client,
     github_repo: &GitHubRepository,
     github_config: &GitHubConfig,
-    issue_number: &String,
+    issue_number: &str,
     labels: &HashSet<String>,
 ) -> Result<(), anyhow::Error> {
-    let client = Client::new();
-
     // Create a URL for the GitHub API endpoint to remove labels for a specific issue
     let url = format!(
         "{}/repos/{}/{}/issues/{}/labels",
@@ -238,33 +606,119 @@ pub async fn remove_labels_from_github_issue(
         // Send a DELETE request for the specific label
         let response = client
             .delete(&label_url)
-            .headers(construct_github_header(&github_config.get_token()))
+            .headers(construct_github_header(&github_config.get_token().await?))
             .header(reqwest::header::ACCEPT, "application/vnd.github.v3+json")
             .send()
             .await?;
 
         // Check the response status for each label and handle errors
         if !response.status().is_success() {
-            let error_msg = format!(
-                "Failed to remove label '{}' from GitHub issue: {:?}",
-                label,
-                response.status()
-            );
-            return Err(anyhow!(error_msg));
+            return Err(http_error(
+                response,
+                format!("Failed to remove label '{}' from GitHub issue", label),
+            )
+            .await);
         }
     }
 
     Ok(())
 }
 
+pub async fn edit_github_issue(
+    client: &Client,
+    github_repo: &GitHubRepository,
+    github_config: &GitHubConfig,
+    issue_number: &str,
+    edit: &IssueEdit,
+) -> Result<(), anyhow::Error> {
+    let url = format!(
+        "{}/repos/{}/{}/issues/{}",
+        github_config.endpoint, github_repo.owner, github_repo.repo, issue_number
+    );
+
+    let mut changes = json!({});
+    if let Some(title) = &edit.title {
+        changes["title"] = json!(title);
+    }
+    if let Some(body) = &edit.body {
+        changes["body"] = json!(body);
+    }
+
+    debug!("github:patch {} {}", url, changes);
+
+    let response = client
+        .patch(&url)
+        .headers(construct_github_header(&github_config.get_token().await?))
+        .json(&changes)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(http_error(response, format!("Unable to edit issue {}", issue_number)).await);
+    }
+
+    println!("Issue {} {} updated.", issue_number, edit.describe());
+    Ok(())
+}
+
+/// A template from `.github/ISSUE_TEMPLATE`, `.md` when the name has no extension
+pub async fn read_issue_template_github(
+    client: &Client,
+    github_repo: &GitHubRepository,
+    github_config: &GitHubConfig,
+    name: &str,
+) -> Result<String, anyhow::Error> {
+    let name = match name.contains('.') {
+        true => name.to_string(),
+        false => format!("{}.md", name),
+    };
+    if name.ends_with(".yml") || name.ends_with(".yaml") {
+        return Err(anyhow!(
+            "{} is an issue form, only Markdown issue templates can be edited",
+            name
+        ));
+    }
+
+    let url = format!(
+        "{}/repos/{}/{}/contents/.github/ISSUE_TEMPLATE/{}",
+        github_config.endpoint, github_repo.owner, github_repo.repo, name
+    );
+
+    debug!("github:get template {}", url);
+
+    let response = client
+        .get(&url)
+        .headers(construct_github_header(&github_config.get_token().await?))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(http_error(
+            response,
+            format!(
+                "Unable to read the issue template {} of {}/{}",
+                name, github_repo.owner, github_repo.repo
+            ),
+        )
+        .await);
+    }
+
+    // the content is wrapped at 60 characters
+    let file: GitHubContent = response.json().await?;
+    let encoded: String = file.content.split_whitespace().collect();
+    let decoded = general_purpose::STANDARD
+        .decode(encoded)
+        .with_context(|| format!("the issue template {} is not base64", name))?;
+    String::from_utf8(decoded).with_context(|| format!("the issue template {} is not text", name))
+}
+
 pub async fn add_comment_to_github_issue(
+    client: &Client,
     github_repo: &GitHubRepository,
     github_config: &GitHubConfig,
-    issue_number: &String,
+    issue_number: &str,
     new_comment: NewComment,
-) -> Result<(), reqwest::Error> {
-    let client = reqwest::Client::new();
-
+) -> Result<(), anyhow::Error> {
     let url = format!(
         "{}/repos/{}/{}/issues/{}/comments",
         github_config.endpoint, github_repo.owner, github_repo.repo, issue_number
@@ -272,16 +726,134 @@ pub async fn add_comment_to_github_issue(
 
     let response = client
         .post(&url)
-        .headers(construct_github_header(&github_config.get_token()))
+        .headers(construct_github_header(&github_config.get_token().await?))
         .json(&new_comment)
         .send()
         .await?;
 
-    if response.status().is_success() {
-        println!("Comment added successfully.");
-    } else {
-        eprintln!("Error: Unable to add a comment to the issue. Status: {:?}", response.status());
+    if !response.status().is_success() {
+        return Err(http_error(
+            response,
+            format!("Unable to add a comment to issue {}", issue_number),
+        )
+        .await);
     }
 
+    println!("Comment added successfully.");
     Ok(())
-}
\ No newline at end of file
+}
+
+/// `GET /user`, the scopes of a classic token come back in `X-OAuth-Scopes`
+pub async fn whoami_github(
+    client: &Client,
+    github_config: &GitHubConfig,
+) -> Result<AuthIdentity, anyhow::Error> {
+    let url = format!("{}/user", github_config.endpoint);
+
+    debug!("github:get user {}", url);
+
+    let response = client
+        .get(&url)
+        .headers(construct_github_header(&github_config.get_token().await?))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(http_error(
+            response,
+            "Unable to read the authenticated user".to_string(),
+        )
+        .await);
+    }
+
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|h| h.to_str().ok())
+            .map(|h| h.to_string())
+    };
+    // fine-grained tokens have no scopes header
+    let scopes = header("X-OAuth-Scopes").map(|s| {
+        s.split(',')
+            .map(|scope| scope.trim().to_string())
+            .filter(|scope| !scope.is_empty())
+            .collect()
+    });
+    let expires = header("github-authentication-token-expiration");
+
+    let user: GitHubUser = response.json().await?;
+    Ok(AuthIdentity {
+        user: user.login,
+        scopes,
+        expires,
+    })
+}
+
+/// `owner/repo` of the repositories the token can see, most recently updated first
+pub async fn list_accessible_repositories_github(
+    client: &Client,
+    github_config: &GitHubConfig,
+) -> Result<Vec<String>, anyhow::Error> {
+    let url = format!(
+        "{}/user/repos?per_page=100&sort=updated",
+        github_config.endpoint
+    );
+
+    debug!("github:get repos {}", url);
+
+    let response = client
+        .get(&url)
+        .headers(construct_github_header(&github_config.get_token().await?))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(http_error(response, "Unable to list the repositories".to_string()).await);
+    }
+
+    let repos: Vec<GitHubRepositorySummary> = response.json().await?;
+    Ok(repos.into_iter().map(|r| r.full_name).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(LINK, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn the_next_page() {
+        let headers = link(
+            "<https://api.github.com/repositories/1/issues?per_page=100&page=2>; rel=\"next\", \
+             <https://api.github.com/repositories/1/issues?per_page=100&page=5>; rel=\"last\"",
+        );
+        assert_eq!(
+            next_page_link(&headers).as_deref(),
+            Some("https://api.github.com/repositories/1/issues?per_page=100&page=2")
+        );
+    }
+
+    #[test]
+    fn a_filter_with_commas() {
+        let headers = link(
+            "<https://api.github.com/repositories/1/issues?page=1&labels=a,b>; rel=\"prev\", \
+             <https://api.github.com/repositories/1/issues?page=3&labels=a,b>; rel=\"next\"",
+        );
+        assert_eq!(
+            next_page_link(&headers).as_deref(),
+            Some("https://api.github.com/repositories/1/issues?page=3&labels=a,b")
+        );
+    }
+
+    #[test]
+    fn the_last_page() {
+        let headers = link("<https://api.github.com/repositories/1/issues?page=1>; rel=\"first\"");
+        assert_eq!(next_page_link(&headers), None);
+        assert_eq!(next_page_link(&HeaderMap::new()), None);
+    }
+}
@@ -1,2 +1,3 @@
 pub mod methods;
 pub mod model;
+pub mod provider;
@@ -1,12 +1,10 @@
-use colored::Color;
 use serde_inline_default::serde_inline_default;
-use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    config::{Defaults, IssueTaskRepository},
-    providers::common::credentials::{CredentialKeyringEntry, HasSecretToken},
+    config::{Defaults, IssueTaskRepository, IssueTemplate, StatusMapping},
+    providers::common::credentials::{Credential, HasSecretToken},
 };
 
 #[derive(Debug, Deserialize, Clone)]
@@ -14,6 +12,11 @@ pub struct GitHubLabel {
     pub name: String,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct GitHubUser {
+    pub login: String,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct GitHubIssue {
     pub number: u32,
@@ -22,12 +25,35 @@ pub struct GitHubIssue {
 
     // Use the new GitLabLabel type for tags
     pub labels: Vec<GitHubLabel>,
+
+    pub state: Option<String>,
+    pub body: Option<String>,
+    pub user: Option<GitHubUser>,
+    #[serde(default)]
+    pub assignees: Vec<GitHubUser>,
+    pub milestone: Option<GitHubMilestone>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub closed_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct GitHubMilestone {
+    pub title: String,
+    pub due_on: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct GitHubComment {
+    pub user: GitHubUser,
+    pub body: Option<String>,
+    pub created_at: String,
 }
 
 #[serde_inline_default]
 #[derive(Debug, Deserialize, Clone)]
 pub struct GitHubConfig {
-    pub credential: Option<CredentialKeyringEntry>,
+    pub credential: Option<Credential>,
 
     // The github endpoint URL
     #[serde_inline_default("https://api.github.com".to_string())]
@@ -45,11 +71,12 @@ impl HasSecretToken for GitHubConfig {
         self.provider_id.clone()
     }
 
-    fn credential(&self) -> Option<CredentialKeyringEntry> {
+    fn credential(&self) -> Option<Credential> {
         self.credential.clone()
     }
 }
 
+#[serde_inline_default]
 #[derive(Debug, Deserialize, Clone)]
 pub struct GitHubRepository {
     /// a unique character across the entire repository config
@@ -71,15 +98,27 @@ pub struct GitHubRepository {
 
     /// filter certain issues
     pub filter: Option<String>,
+
+    /// Stop paging through the issues once this many have been read
+    #[serde_inline_default(500)]
+    pub max_issues: usize,
+
+    /// The labels of todo, doing and done
+    #[serde(default)]
+    pub status: StatusMapping,
+
+    /// What a new issue starts from in $EDITOR
+    pub template: Option<IssueTemplate>,
 }
 
 impl IssueTaskRepository for GitHubRepository {
+    fn template(&self) -> Option<&IssueTemplate> {
+        self.template.as_ref()
+    }
+
     fn defaults(&self) -> Option<Defaults> {
         self.defaults.clone()
     }
-    fn color(&self) -> Color {
-        Color::from_str(&self.color).unwrap()
-    }
 
     fn id(&self) -> String {
         self.id.clone()
@@ -90,4 +129,16 @@ impl IssueTaskRepository for GitHubRepository {
 #[derive(Deserialize, Serialize)]
 pub struct NewComment {
     pub body: String,
-}
\ No newline at end of file
+}
+
+/// A file from `GET /repos/{owner}/{repo}/contents/{path}`, base64 encoded
+#[derive(Debug, Deserialize, Clone)]
+pub struct GitHubContent {
+    pub content: String,
+}
+
+/// A repository the token can see, from `GET /user/repos`
+#[derive(Debug, Deserialize, Clone)]
+pub struct GitHubRepositorySummary {
+    pub full_name: String,
+}
@@ -0,0 +1,150 @@
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use reqwest::Client;
+
+use crate::config::IssueTaskRepository;
+use crate::git_remote::GitRemote;
+use crate::providers::common::{
+    model::{Assignee, Closure, Issue, IssueDetails, IssueEdit, WorkStatus},
+    provider::IssueProvider,
+    status::set_status_by_labels,
+};
+
+use super::methods::{
+    add_comment_to_github_issue, add_labels_to_github_issue, add_new_task_github,
+    assign_github_issue, close_task_github, collect_closed_tasks_from_github,
+    collect_tasks_from_github, edit_github_issue, get_issue_details_github, get_issue_github,
+    read_issue_template_github, remove_labels_from_github_issue, reopen_task_github,
+    unassign_github_issue,
+};
+use super::model::{GitHubConfig, GitHubRepository, NewComment};
+
+/// A GitHub repository, and the GitHub configuration it was declared under
+pub struct GitHubIssueStore {
+    /// shared across every store, so all requests use one connection pool
+    pub client: Client,
+    pub config: GitHubConfig,
+    pub repo: GitHubRepository,
+}
+
+#[async_trait]
+impl IssueProvider for GitHubIssueStore {
+    fn repository(&self) -> &dyn IssueTaskRepository {
+        &self.repo
+    }
+
+    fn provider(&self) -> &'static str {
+        "github"
+    }
+
+    fn location(&self) -> String {
+        format!(
+            "{}/{}/{}",
+            self.config.endpoint, self.repo.owner, self.repo.repo
+        )
+    }
+
+    fn matches_remote(&self, remote: &GitRemote) -> bool {
+        remote.is_on(&self.config.endpoint)
+            && remote.is_path(&format!("{}/{}", self.repo.owner, self.repo.repo))
+    }
+
+    async fn list_issues(&self) -> Result<Vec<Issue>, anyhow::Error> {
+        collect_tasks_from_github(&self.client, &self.config, &self.repo).await
+    }
+
+    async fn get_issue(&self, issue_id: &str) -> Result<Issue, anyhow::Error> {
+        get_issue_github(&self.client, &self.config, &self.repo, issue_id).await
+    }
+
+    async fn get_issue_details(&self, issue_id: &str) -> Result<IssueDetails, anyhow::Error> {
+        get_issue_details_github(&self.client, &self.config, &self.repo, issue_id).await
+    }
+
+    async fn create_issue(
+        &self,
+        title: &str,
+        details: &str,
+        tags: &Option<Vec<String>>,
+        assignee: Option<&Assignee>,
+    ) -> Result<Issue, anyhow::Error> {
+        add_new_task_github(
+            &self.client,
+            &self.repo,
+            &self.config,
+            title,
+            details,
+            tags,
+            assignee,
+        )
+        .await
+    }
+
+    async fn close_issue(&self, issue_id: &str, closure: &Closure) -> Result<(), anyhow::Error> {
+        close_task_github(&self.client, &self.config, &self.repo, issue_id, closure).await
+    }
+
+    async fn reopen_issue(&self, issue_id: &str) -> Result<(), anyhow::Error> {
+        reopen_task_github(&self.client, &self.config, &self.repo, issue_id).await
+    }
+
+    async fn read_repo_template(&self, name: &str) -> Result<String, anyhow::Error> {
+        read_issue_template_github(&self.client, &self.repo, &self.config, name).await
+    }
+
+    async fn set_status(&self, issue_id: &str, status: WorkStatus) -> Result<(), anyhow::Error> {
+        set_status_by_labels(self, &self.repo.status, issue_id, status).await
+    }
+
+    async fn list_closed_issues(&self, limit: usize) -> Result<Vec<Issue>, anyhow::Error> {
+        collect_closed_tasks_from_github(&self.client, &self.config, &self.repo, limit).await
+    }
+
+    async fn edit_issue(&self, issue_id: &str, edit: &IssueEdit) -> Result<(), anyhow::Error> {
+        edit_github_issue(&self.client, &self.repo, &self.config, issue_id, edit).await
+    }
+
+    async fn assign_issue(&self, issue_id: &str, assignee: &Assignee) -> Result<(), anyhow::Error> {
+        assign_github_issue(&self.client, &self.repo, &self.config, issue_id, assignee).await
+    }
+
+    async fn unassign_issue(
+        &self,
+        issue_id: &str,
+        assignee: Option<&Assignee>,
+    ) -> Result<(), anyhow::Error> {
+        unassign_github_issue(&self.client, &self.repo, &self.config, issue_id, assignee).await
+    }
+
+    async fn add_comment(&self, issue_id: &str, comment: &str) -> Result<(), anyhow::Error> {
+        let new_comment = NewComment {
+            body: comment.to_string(),
+        };
+        Ok(add_comment_to_github_issue(
+            &self.client,
+            &self.repo,
+            &self.config,
+            issue_id,
+            new_comment,
+        )
+        .await?)
+    }
+
+    async fn add_labels(
+        &self,
+        issue_id: &str,
+        labels: &HashSet<String>,
+    ) -> Result<(), anyhow::Error> {
+        add_labels_to_github_issue(&self.client, &self.repo, &self.config, issue_id, labels).await
+    }
+
+    async fn remove_labels(
+        &self,
+        issue_id: &str,
+        labels: &HashSet<String>,
+    ) -> Result<(), anyhow::Error> {
+        remove_labels_from_github_issue(&self.client, &self.repo, &self.config, issue_id, labels)
+            .await
+    }
+}
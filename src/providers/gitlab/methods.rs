@@ -1,9 +1,16 @@
 use std::collections::HashSet;
 
+use crate::errors::http_error;
 use crate::providers::common::credentials::HasSecretToken;
-use crate::providers::common::model::Issue;
+use crate::providers::common::model::{
+    parse_due_date, parse_timestamp, Assignee, AuthIdentity, Closure, Comment, Issue, IssueDetails,
+    IssueEdit,
+};
 use crate::providers::gitlab::model::GitLabConfig;
-use crate::providers::gitlab::model::GitLabIssue;
+use crate::providers::gitlab::model::{
+    GitLabAccessToken, GitLabIssue, GitLabIssueTemplate, GitLabNote, GitLabProjectSummary,
+    GitLabUser,
+};
 
 use anyhow::{anyhow, Result};
 use serde_json::json;
@@ -23,115 +30,359 @@ pub fn construct_gitlab_header(token: &str) -> HeaderMap {
 }
 
 pub async fn close_task_gitlab(
+    client: &Client,
     gitlab_config: &GitLabConfig,
     repo_config: &GitLabRepository,
-    issue_id: &String,
+    issue_id: &str,
+    closure: &Closure,
 ) -> Result<(), anyhow::Error> {
-    let client: Client = Client::new();
+    // GitLab has no close reason, so it goes in a note along with the comment
+    if let Some(note) = closure.note() {
+        add_comment_to_gitlab_issue(client, repo_config, gitlab_config, issue_id, &note).await?;
+    }
 
     let url = format!(
         "{}/api/v4/projects/{}/issues/{}?state_event=close",
-        &gitlab_config.endpoint, repo_config.project_id, &issue_id
+        gitlab_config.endpoint, repo_config.project_id, issue_id
     );
 
     debug!("gitlab: will close {}", url);
 
     let response = client
         .put(&url)
-        .headers(construct_gitlab_header(&gitlab_config.get_token()))
+        .headers(construct_gitlab_header(&gitlab_config.get_token().await?))
         .send()
         .await?;
 
-    if response.status().is_success() {
-        println!(
-            "Task {} closed in GitLab project: {}",
-            issue_id, repo_config.project_id
-        );
-    } else {
-        println!(
-            "Error: Unable to close {} issue in GitLab project: {}. Status: {:?}",
-            issue_id,
-            repo_config.project_id,
-            response.status()
-        );
+    if !response.status().is_success() {
+        return Err(http_error(
+            response,
+            format!(
+                "Unable to close issue {} in GitLab project {}",
+                issue_id, repo_config.project_id
+            ),
+        )
+        .await);
+    }
+
+    println!(
+        "Task {} closed in GitLab project: {}",
+        issue_id, repo_config.project_id
+    );
+    Ok(())
+}
+
+pub async fn reopen_task_gitlab(
+    client: &Client,
+    gitlab_config: &GitLabConfig,
+    repo_config: &GitLabRepository,
+    issue_id: &str,
+) -> Result<(), anyhow::Error> {
+    let url = format!(
+        "{}/api/v4/projects/{}/issues/{}?state_event=reopen",
+        gitlab_config.endpoint, repo_config.project_id, issue_id
+    );
+
+    debug!("gitlab: will reopen {}", url);
+
+    let response = client
+        .put(&url)
+        .headers(construct_gitlab_header(&gitlab_config.get_token().await?))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(http_error(
+            response,
+            format!(
+                "Unable to reopen issue {} in GitLab project {}",
+                issue_id, repo_config.project_id
+            ),
+        )
+        .await);
     }
+
+    println!(
+        "Task {} reopened in GitLab project: {}",
+        issue_id, repo_config.project_id
+    );
     Ok(())
 }
 
+/// The most recently closed issues, newest first (one page, after the configured filter)
+pub async fn collect_closed_tasks_from_gitlab(
+    client: &Client,
+    gitlab_config: &GitLabConfig,
+    repo: &GitLabRepository,
+    limit: usize,
+) -> Result<Vec<Issue>, anyhow::Error> {
+    let optional_filter = repo
+        .filter
+        .as_ref()
+        .map_or("".to_string(), |filt| format!("&{}", filt));
+
+    let url = format!(
+        "{}/api/v4/projects/{}/issues?state=closed&order_by=updated_at&sort=desc&per_page={}{}",
+        gitlab_config.endpoint,
+        repo.project_id,
+        limit.clamp(1, 100),
+        optional_filter
+    );
+
+    debug!("gitlab:get closed issues {}", url);
+
+    let response = client
+        .get(&url)
+        .headers(construct_gitlab_header(&gitlab_config.get_token().await?))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(http_error(
+            response,
+            format!(
+                "Unable to fetch closed issues for project_id {}",
+                repo.project_id
+            ),
+        )
+        .await);
+    }
+
+    let gitlab_issues: Vec<GitLabIssue> = response.json().await?;
+    Ok(gitlab_issues
+        .into_iter()
+        .map(|gitlab_issue| to_issue(repo, gitlab_issue))
+        .collect())
+}
+
+fn to_issue(repo: &GitLabRepository, gitlab_issue: GitLabIssue) -> Issue {
+    let status = repo.status.status_of_labels(
+        gitlab_issue.labels.iter().map(|l| l.0.as_str()),
+        gitlab_issue.state.as_deref() == Some("closed"),
+    );
+    Issue {
+        id: format!("{}/{}", repo.id, gitlab_issue.iid),
+        store_id: repo.id.clone(),
+        provider: "gitlab".to_string(),
+        title: gitlab_issue.title,
+        html_url: gitlab_issue.web_url,
+        tags: gitlab_issue
+            .labels
+            .into_iter()
+            .map(|label| Label { name: label.0 })
+            .collect(),
+        state: gitlab_issue.state.unwrap_or_default(),
+        assignees: gitlab_issue
+            .assignees
+            .into_iter()
+            .map(|a| a.username)
+            .collect(),
+        author: gitlab_issue.author.map(|a| a.username),
+        created_at: gitlab_issue.created_at.as_deref().and_then(parse_timestamp),
+        updated_at: gitlab_issue.updated_at.as_deref().and_then(parse_timestamp),
+        closed_at: gitlab_issue.closed_at.as_deref().and_then(parse_timestamp),
+        status: Some(status),
+        due: gitlab_issue.due_date.as_deref().and_then(parse_due_date),
+        milestone: gitlab_issue.milestone.map(|m| m.title),
+        description: gitlab_issue.description,
+    }
+}
+
 pub async fn collect_tasks_from_gitlab(
-    gitlab_config: &Vec<GitLabConfig>,
-    issue_store_id: &Option<String>,
+    client: &Client,
+    gitlab_config: &GitLabConfig,
+    repo: &GitLabRepository,
 ) -> Result<Vec<Issue>, anyhow::Error> {
-    let client: Client = Client::new();
-    let mut all_issues = Vec::new();
-
-    for g in gitlab_config {
-        for (_idx, repo) in g
-            .repositories
-            .iter()
-            .filter(|&r| issue_store_id.is_none() || issue_store_id.as_deref().is_some_and(|p| r.id == p))
-            .enumerate()
-        {
-            let optional_filter = repo
-                .filter
-                .as_ref()
-                .map_or("".to_string(), |filt| format!("&{}", filt));
-
-            let url = format!(
-                "{}/api/v4/projects/{}/issues?state=opened{}",
-                g.endpoint, repo.project_id, optional_filter
-            );
-
-            debug!("gitlab:get issues {}", url);
-
-            let response = client
-                .get(&url)
-                .header("PRIVATE-TOKEN", g.get_token())
-                .send()
-                .await?;
-
-            if response.status().is_success() {
-                let body = response.text().await?;
-                debug!("{}", body);
-
-                let gitlab_issues: Vec<GitLabIssue> = serde_json::from_str(&body)?;
-                // Convert GitLab issues to the internal Issue representation
-                let issues = gitlab_issues.into_iter().map(|gitlab_issue| Issue {
-                    id: format!("{}/{}", repo.id, gitlab_issue.iid),
-                    title: gitlab_issue.title,
-                    html_url: gitlab_issue.web_url,
-                    tags: gitlab_issue
-                        .labels
-                        .into_iter()
-                        .map(|label| Label { name: label.0 })
-                        .collect(),
-                });
-
-                all_issues.extend(issues);
-            } else {
-                println!(
-                    "Error: Unable to fetch issues for project_id {}. Status: {:?}",
-                    repo.project_id,
-                    response.status()
-                );
-            }
+    let optional_filter = repo
+        .filter
+        .as_ref()
+        .map_or("".to_string(), |filt| format!("&{}", filt));
+
+    let url = format!(
+        "{}/api/v4/projects/{}/issues?state=opened&per_page={}{}",
+        gitlab_config.endpoint,
+        repo.project_id,
+        repo.max_issues.clamp(1, 100),
+        optional_filter
+    );
+
+    let mut next_page = Some("1".to_string());
+    let mut issues = Vec::new();
+    let mut truncated = false;
+
+    // walk the X-Next-Page headers until we run out of pages, or reach max_issues
+    while let Some(page) = next_page.take() {
+        if issues.len() >= repo.max_issues {
+            truncated = true;
+            break;
+        }
+
+        let page_url = format!("{}&page={}", url, page);
+        debug!("gitlab:get issues {}", page_url);
+
+        let response = client
+            .get(&page_url)
+            .headers(construct_gitlab_header(&gitlab_config.get_token().await?))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(http_error(
+                response,
+                format!("Unable to fetch issues for project_id {}", repo.project_id),
+            )
+            .await);
+        }
+
+        next_page = response
+            .headers()
+            .get("X-Next-Page")
+            .and_then(|h| h.to_str().ok())
+            .filter(|p| !p.is_empty())
+            .map(|p| p.to_string());
+
+        let body = response.text().await?;
+        debug!("{}", body);
+
+        let gitlab_issues: Vec<GitLabIssue> = serde_json::from_str(&body)?;
+        // Convert GitLab issues to the internal Issue representation
+        issues.extend(
+            gitlab_issues
+                .into_iter()
+                .map(|gitlab_issue| to_issue(repo, gitlab_issue)),
+        );
+    }
+
+    if issues.len() > repo.max_issues {
+        truncated = true;
+        issues.truncate(repo.max_issues);
+    }
+
+    if truncated {
+        eprintln!(
+            "Warning: only the first {} issues of {} ({}) are shown, raise max_issues to see more",
+            repo.max_issues, repo.id, repo.project_id
+        );
+    }
+
+    Ok(issues)
+}
+
+async fn fetch_gitlab_issue(
+    client: &Client,
+    gitlab_config: &GitLabConfig,
+    repo: &GitLabRepository,
+    issue_iid: &str,
+) -> Result<GitLabIssue, anyhow::Error> {
+    let url = format!(
+        "{}/api/v4/projects/{}/issues/{}",
+        gitlab_config.endpoint, repo.project_id, issue_iid
+    );
+
+    debug!("gitlab:get issue {}", url);
+
+    let response = client
+        .get(&url)
+        .headers(construct_gitlab_header(&gitlab_config.get_token().await?))
+        .send()
+        .await?;
+
+    if response.status().is_success() {
+        Ok(response.json::<GitLabIssue>().await?)
+    } else {
+        Err(http_error(
+            response,
+            format!(
+                "Unable to fetch issue {} from GitLab project {}",
+                issue_iid, repo.project_id
+            ),
+        )
+        .await)
+    }
+}
+
+pub async fn get_issue_gitlab(
+    client: &Client,
+    gitlab_config: &GitLabConfig,
+    repo: &GitLabRepository,
+    issue_iid: &str,
+) -> Result<Issue, anyhow::Error> {
+    let gitlab_issue = fetch_gitlab_issue(client, gitlab_config, repo, issue_iid).await?;
+    Ok(to_issue(repo, gitlab_issue))
+}
+
+pub async fn get_issue_details_gitlab(
+    client: &Client,
+    gitlab_config: &GitLabConfig,
+    repo: &GitLabRepository,
+    issue_iid: &str,
+) -> Result<IssueDetails, anyhow::Error> {
+    let gitlab_issue = fetch_gitlab_issue(client, gitlab_config, repo, issue_iid).await?;
+
+    let url = format!(
+        "{}/api/v4/projects/{}/issues/{}/notes?sort=asc&per_page=100",
+        gitlab_config.endpoint, repo.project_id, issue_iid
+    );
+
+    let mut next_page = Some("1".to_string());
+    let mut notes: Vec<GitLabNote> = Vec::new();
+
+    // a long discussion is more than one page
+    while let Some(page) = next_page.take() {
+        let page_url = format!("{}&page={}", url, page);
+        debug!("gitlab:get notes {}", page_url);
+
+        let response = client
+            .get(&page_url)
+            .headers(construct_gitlab_header(&gitlab_config.get_token().await?))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(http_error(
+                response,
+                format!(
+                    "Unable to fetch the notes of issue {} from GitLab project {}",
+                    issue_iid, repo.project_id
+                ),
+            )
+            .await);
         }
+
+        next_page = response
+            .headers()
+            .get("X-Next-Page")
+            .and_then(|h| h.to_str().ok())
+            .filter(|p| !p.is_empty())
+            .map(|p| p.to_string());
+        notes.extend(response.json::<Vec<GitLabNote>>().await?);
     }
 
-    Ok(all_issues)
+    Ok(IssueDetails {
+        issue: to_issue(repo, gitlab_issue),
+        comments: notes
+            .into_iter()
+            .filter(|n| !n.system)
+            .map(|n| Comment {
+                author: n.author.username,
+                created_at: parse_timestamp(&n.created_at),
+                body: n.body,
+            })
+            .collect(),
+    })
 }
 
 pub async fn add_new_task_gitlab(
+    client: &Client,
     gitlab_repo: &GitLabRepository,
     gitlab_config: &GitLabConfig,
     title: &str,
     details: &str,
     tags: &Option<Vec<String>>,
-) -> Result<(), anyhow::Error> {
+    assignee: Option<&Assignee>,
+) -> Result<Issue, anyhow::Error> {
     debug!("Adding a new task via gitlab: {} [{:?}]", &title, &tags);
 
-    let client = Client::new();
-
     let add_url = format!(
         "{}/api/v4/projects/{}/issues",
         gitlab_config.endpoint, gitlab_repo.project_id
@@ -146,38 +397,163 @@ pub async fn add_new_task_gitlab(
         issue_details["labels"] = ts.to_vec().into();
     }
 
+    if let Some(assignee) = assignee {
+        let user = gitlab_user(client, gitlab_config, assignee).await?;
+        issue_details["assignee_ids"] = json!([user.id]);
+    }
+
     let response = client
         .post(&add_url)
-        .headers(construct_gitlab_header(&gitlab_config.get_token()))
+        .headers(construct_gitlab_header(&gitlab_config.get_token().await?))
         .header(reqwest::header::CONTENT_TYPE, "application/json")
         .json(&issue_details)
         .send()
         .await?;
 
     if response.status().is_success() {
-        // debug!("{}",response.status());
-        // let t = response.text().await?;
         let issue: GitLabIssue = response.json::<GitLabIssue>().await?;
-        println!("New issue created:");
-        println!("Title: {}", issue.title);
-        println!("URL: {}", issue.web_url);
+        Ok(to_issue(gitlab_repo, issue))
     } else {
+        Err(http_error(response, "Unable to create issue".to_string()).await)
+    }
+}
+
+/// A user by username, `--me` being the user of the token
+async fn gitlab_user(
+    client: &Client,
+    gitlab_config: &GitLabConfig,
+    assignee: &Assignee,
+) -> Result<GitLabUser, anyhow::Error> {
+    let request = match assignee {
+        Assignee::Me => client.get(format!("{}/api/v4/user", gitlab_config.endpoint)),
+        Assignee::User(username) => client
+            .get(format!("{}/api/v4/users", gitlab_config.endpoint))
+            .query(&[("username", username.trim_start_matches('@'))]),
+    };
+
+    debug!("gitlab:get user {}", assignee);
+
+    let response = request
+        .headers(construct_gitlab_header(&gitlab_config.get_token().await?))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(http_error(response, format!("Unable to find the user {}", assignee)).await);
+    }
+
+    match assignee {
+        Assignee::Me => Ok(response.json::<GitLabUser>().await?),
+        Assignee::User(username) => response
+            .json::<Vec<GitLabUser>>()
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("there is no GitLab user {}", username)),
+    }
+}
+
+/// GitLab replaces the assignees, with all of `assignee_ids`
+async fn set_gitlab_assignees(
+    client: &Client,
+    gitlab_repo: &GitLabRepository,
+    gitlab_config: &GitLabConfig,
+    issue_iid: &str,
+    assignee_ids: &[u64],
+) -> Result<(), anyhow::Error> {
+    let url = format!(
+        "{}/api/v4/projects/{}/issues/{}",
+        gitlab_config.endpoint, gitlab_repo.project_id, issue_iid
+    );
+
+    debug!("gitlab:assign {} {:?}", url, assignee_ids);
+
+    let response = client
+        .put(&url)
+        .headers(construct_gitlab_header(&gitlab_config.get_token().await?))
+        .json(&json!({ "assignee_ids": assignee_ids }))
+        .send()
+        .await?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(http_error(
+            response,
+            format!("Unable to change the assignees of issue {}", issue_iid),
+        )
+        .await)
+    }
+}
+
+pub async fn assign_gitlab_issue(
+    client: &Client,
+    gitlab_repo: &GitLabRepository,
+    gitlab_config: &GitLabConfig,
+    issue_iid: &str,
+    assignee: &Assignee,
+) -> Result<(), anyhow::Error> {
+    let user = gitlab_user(client, gitlab_config, assignee).await?;
+    let issue = fetch_gitlab_issue(client, gitlab_config, gitlab_repo, issue_iid).await?;
+
+    let mut assignee_ids: Vec<u64> = issue.assignees.iter().map(|a| a.id).collect();
+    if assignee_ids.contains(&user.id) {
         println!(
-            "Error: Unable to create issue. Status: {:?}",
-            response.status()
+            "Issue {} is already assigned to {}.",
+            issue_iid, user.username
         );
+        return Ok(());
     }
+    assignee_ids.push(user.id);
+
+    set_gitlab_assignees(client, gitlab_repo, gitlab_config, issue_iid, &assignee_ids).await?;
+    println!("Issue {} assigned to {}.", issue_iid, user.username);
+    Ok(())
+}
+
+pub async fn unassign_gitlab_issue(
+    client: &Client,
+    gitlab_repo: &GitLabRepository,
+    gitlab_config: &GitLabConfig,
+    issue_iid: &str,
+    assignee: Option<&Assignee>,
+) -> Result<(), anyhow::Error> {
+    let issue = fetch_gitlab_issue(client, gitlab_config, gitlab_repo, issue_iid).await?;
+
+    let (removed, remaining): (Vec<GitLabUser>, Vec<GitLabUser>) = match assignee {
+        None => (issue.assignees, Vec::new()),
+        Some(assignee) => {
+            let user = gitlab_user(client, gitlab_config, assignee).await?;
+            issue.assignees.into_iter().partition(|a| a.id == user.id)
+        }
+    };
+    if removed.is_empty() {
+        match assignee {
+            Some(assignee) => println!("Issue {} is not assigned to {}.", issue_iid, assignee),
+            None => println!("Issue {} has no assignees.", issue_iid),
+        }
+        return Ok(());
+    }
+
+    let assignee_ids: Vec<u64> = remaining.iter().map(|a| a.id).collect();
+    set_gitlab_assignees(client, gitlab_repo, gitlab_config, issue_iid, &assignee_ids).await?;
+
+    let usernames: Vec<String> = removed.into_iter().map(|a| a.username).collect();
+    println!(
+        "Issue {} unassigned from {}.",
+        issue_iid,
+        usernames.join(", ")
+    );
     Ok(())
 }
 
 pub async fn add_labels_to_gitlab_issue(
+    client: &Client,
     gitlab_repo: &GitLabRepository,
     gitlab_config: &GitLabConfig,
-    issue_id: &String,
+    issue_id: &str,
     tags: &HashSet<String>,
 ) -> Result<(), anyhow::Error> {
-    let client = Client::new();
-
     // Create a URL for the GitLab API endpoint to add labels
     let url = format!(
         "{}/api/v4/projects/{}/issues/{}/add_labels",
@@ -193,7 +569,7 @@ pub async fn add_labels_to_gitlab_issue(
     // Send a POST request to add labels
     let response = client
         .post(&url)
-        .headers(construct_gitlab_header(&gitlab_config.get_token()))
+        .headers(construct_gitlab_header(&gitlab_config.get_token().await?))
         .header(reqwest::header::CONTENT_TYPE, "application/json")
         .json(&json_body)
         .send()
@@ -203,22 +579,17 @@ pub async fn add_labels_to_gitlab_issue(
     if response.status().is_success() {
         Ok(())
     } else {
-        let error_msg = format!(
-            "Failed to add labels to GitLab issue: {:?}",
-            response.status()
-        );
-        Err(anyhow!(error_msg))
+        Err(http_error(response, "Failed to add labels to GitLab issue".to_string()).await)
     }
 }
 
 pub async fn remove_labels_from_gitlab_issue(
+    client: &Client,
     gitlab_repo: &GitLabRepository,
     gitlab_config: &GitLabConfig,
-    issue_id: &String,
+    issue_id: &str,
     tags: &HashSet<String>,
 ) -> Result<(), anyhow::Error> {
-    let client = Client::new();
-
     // Create a URL for the GitLab API endpoint to add labels
     let url = format!(
         "{}/api/v4/projects/{}/issues/{}/remove_labels",
@@ -234,7 +605,7 @@ pub async fn remove_labels_from_gitlab_issue(
     // Send a POST request to add labels
     let response = client
         .post(&url)
-        .headers(construct_gitlab_header(&gitlab_config.get_token()))
+        .headers(construct_gitlab_header(&gitlab_config.get_token().await?))
         .header(reqwest::header::CONTENT_TYPE, "application/json")
         .json(&json_body)
         .send()
@@ -244,23 +615,93 @@ pub async fn remove_labels_from_gitlab_issue(
     if response.status().is_success() {
         Ok(())
     } else {
-        let error_msg = format!(
-            "Failed to remove labels from GitLab issue: {:?}",
-            response.status()
-        );
-        Err(anyhow!(error_msg))
+        Err(http_error(
+            response,
+            "Failed to remove labels from GitLab issue".to_string(),
+        )
+        .await)
     }
 }
 
+pub async fn edit_gitlab_issue(
+    client: &Client,
+    gitlab_repo: &GitLabRepository,
+    gitlab_config: &GitLabConfig,
+    issue_iid: &str,
+    edit: &IssueEdit,
+) -> Result<(), anyhow::Error> {
+    let url = format!(
+        "{}/api/v4/projects/{}/issues/{}",
+        gitlab_config.endpoint, gitlab_repo.project_id, issue_iid
+    );
+
+    let mut changes = json!({});
+    if let Some(title) = &edit.title {
+        changes["title"] = json!(title);
+    }
+    if let Some(body) = &edit.body {
+        changes["description"] = json!(body);
+    }
+
+    debug!("gitlab:put {} {}", url, changes);
+
+    let response = client
+        .put(&url)
+        .headers(construct_gitlab_header(&gitlab_config.get_token().await?))
+        .json(&changes)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(http_error(response, format!("Unable to edit issue {}", issue_iid)).await);
+    }
+
+    println!("Issue {} {} updated.", issue_iid, edit.describe());
+    Ok(())
+}
+
+/// A template from `.gitlab/issue_templates`, by its name without the `.md`
+pub async fn read_issue_template_gitlab(
+    client: &Client,
+    gitlab_repo: &GitLabRepository,
+    gitlab_config: &GitLabConfig,
+    name: &str,
+) -> Result<String, anyhow::Error> {
+    let name = name.strip_suffix(".md").unwrap_or(name);
+    let url = format!(
+        "{}/api/v4/projects/{}/templates/issues/{}",
+        gitlab_config.endpoint, gitlab_repo.project_id, name
+    );
+
+    debug!("gitlab:get template {}", url);
+
+    let response = client
+        .get(&url)
+        .headers(construct_gitlab_header(&gitlab_config.get_token().await?))
+        .send()
+        .await?;
+
+    if response.status().is_success() {
+        Ok(response.json::<GitLabIssueTemplate>().await?.content)
+    } else {
+        Err(http_error(
+            response,
+            format!(
+                "Unable to read the issue template {} of GitLab project {}",
+                name, gitlab_repo.project_id
+            ),
+        )
+        .await)
+    }
+}
 
 pub async fn add_comment_to_gitlab_issue(
+    client: &Client,
     gitlab_repo: &GitLabRepository,
     gitlab_config: &GitLabConfig,
-    issue_iid: &String,
+    issue_iid: &str,
     comment: &str,
-) -> Result<(), reqwest::Error> {
-    let client = reqwest::Client::new();
-
+) -> Result<(), anyhow::Error> {
     // Construct the GitLab API URL for creating a new comment
     let url = format!(
         "{}/api/v4/projects/{}/issues/{}/notes",
@@ -272,16 +713,100 @@ pub async fn add_comment_to_gitlab_issue(
 
     let response = client
         .post(&url)
-        .headers(construct_gitlab_header(&gitlab_config.get_token()))
+        .headers(construct_gitlab_header(&gitlab_config.get_token().await?))
         .multipart(form)
         .send()
         .await?;
 
-    if response.status().is_success() {
-        println!("Comment added successfully.");
-    } else {
-        eprintln!("Error: Unable to add a comment to the issue. Status: {:?}", response.status());
+    if !response.status().is_success() {
+        return Err(http_error(
+            response,
+            format!("Unable to add a comment to issue {}", issue_iid),
+        )
+        .await);
     }
 
+    println!("Comment added successfully.");
     Ok(())
-}
\ No newline at end of file
+}
+
+/// `GET /user`, then the token's scopes and expiry from `/personal_access_tokens/self`
+pub async fn whoami_gitlab(
+    client: &Client,
+    gitlab_config: &GitLabConfig,
+) -> Result<AuthIdentity, anyhow::Error> {
+    let token = gitlab_config.get_token().await?;
+    let url = format!("{}/api/v4/user", gitlab_config.endpoint);
+
+    debug!("gitlab:get user {}", url);
+
+    let response = client
+        .get(&url)
+        .headers(construct_gitlab_header(&token))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(http_error(
+            response,
+            "Unable to read the authenticated user".to_string(),
+        )
+        .await);
+    }
+
+    let user: GitLabUser = response.json().await?;
+
+    let url = format!(
+        "{}/api/v4/personal_access_tokens/self",
+        gitlab_config.endpoint
+    );
+
+    debug!("gitlab:get token {}", url);
+
+    let response = client
+        .get(&url)
+        .headers(construct_gitlab_header(&token))
+        .send()
+        .await?;
+
+    // not every token is a personal access token (and older GitLabs lack the endpoint)
+    let access_token: Option<GitLabAccessToken> = match response.status().is_success() {
+        true => Some(response.json().await?),
+        false => None,
+    };
+
+    Ok(AuthIdentity {
+        user: user.username,
+        scopes: access_token.as_ref().map(|t| t.scopes.clone()),
+        expires: access_token.and_then(|t| t.expires_at),
+    })
+}
+
+/// `group/project` of the projects the token is a member of, most recently active first
+pub async fn list_accessible_projects_gitlab(
+    client: &Client,
+    gitlab_config: &GitLabConfig,
+) -> Result<Vec<String>, anyhow::Error> {
+    let url = format!(
+        "{}/api/v4/projects?membership=true&per_page=100&order_by=last_activity_at",
+        gitlab_config.endpoint
+    );
+
+    debug!("gitlab:get projects {}", url);
+
+    let response = client
+        .get(&url)
+        .headers(construct_gitlab_header(&gitlab_config.get_token().await?))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(http_error(response, "Unable to list the projects".to_string()).await);
+    }
+
+    let projects: Vec<GitLabProjectSummary> = response.json().await?;
+    Ok(projects
+        .into_iter()
+        .map(|p| p.path_with_namespace)
+        .collect())
+}
@@ -1,18 +1,16 @@
-use colored::Color;
 use serde::Deserialize;
 use serde_inline_default::serde_inline_default;
-use std::str::FromStr;
 
 use crate::{
-    config::{Defaults, IssueTaskRepository},
-    providers::common::credentials::{CredentialKeyringEntry, HasSecretToken},
+    config::{Defaults, IssueTaskRepository, IssueTemplate, StatusMapping},
+    providers::common::credentials::{Credential, HasSecretToken},
 };
 
 
 #[serde_inline_default]
 #[derive(Debug, Deserialize, Clone)]
 pub struct GitLabConfig {
-    pub credential: Option<CredentialKeyringEntry>,
+    pub credential: Option<Credential>,
 
     #[serde_inline_default("https://gitlab.com".to_string())]
     pub endpoint: String,
@@ -29,7 +27,7 @@ impl HasSecretToken for GitLabConfig {
         self.provider_id.clone()
     }
 
-    fn credential(&self) -> Option<CredentialKeyringEntry> {
+    fn credential(&self) -> Option<Credential> {
         self.credential.clone()
     }
 }
@@ -39,6 +37,20 @@ impl HasSecretToken for GitLabConfig {
 #[derive(Debug, Deserialize, Clone)]
 pub struct GitLabLabel(pub String);
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct GitLabUser {
+    pub id: u64,
+    pub username: String,
+}
+
+/// `GET /personal_access_tokens/self`
+#[derive(Debug, Deserialize, Clone)]
+pub struct GitLabAccessToken {
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    pub expires_at: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct GitLabIssue {
     pub iid: u32,
@@ -47,8 +59,36 @@ pub struct GitLabIssue {
 
     // Use the new GitLabLabel type for tags
     pub labels: Vec<GitLabLabel>,
+
+    pub state: Option<String>,
+    pub description: Option<String>,
+    pub author: Option<GitLabUser>,
+    #[serde(default)]
+    pub assignees: Vec<GitLabUser>,
+    pub milestone: Option<GitLabMilestone>,
+    pub due_date: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub closed_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct GitLabMilestone {
+    pub title: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct GitLabNote {
+    pub author: GitLabUser,
+    pub body: String,
+    pub created_at: String,
+
+    /// notes GitLab generates itself ("added label x", "closed")
+    #[serde(default)]
+    pub system: bool,
 }
 
+#[serde_inline_default]
 #[derive(Debug, Deserialize, Clone)]
 pub struct GitLabRepository {
     /// a unique character across the entire repository config
@@ -68,18 +108,41 @@ pub struct GitLabRepository {
 
     /// filter certain issues
     pub filter: Option<String>,
+
+    /// Stop paging through the issues once this many have been read
+    #[serde_inline_default(500)]
+    pub max_issues: usize,
+
+    /// The labels of todo, doing and done
+    #[serde(default)]
+    pub status: StatusMapping,
+
+    /// What a new issue starts from in $EDITOR
+    pub template: Option<IssueTemplate>,
 }
 
 impl IssueTaskRepository for GitLabRepository {
-    fn defaults(&self) -> Option<Defaults> {
-        self.defaults.clone()
+    fn template(&self) -> Option<&IssueTemplate> {
+        self.template.as_ref()
     }
 
-    fn color(&self) -> Color {
-        Color::from_str(&self.color).unwrap()
+    fn defaults(&self) -> Option<Defaults> {
+        self.defaults.clone()
     }
 
     fn id(&self) -> String {
         self.id.clone()
     }
 }
+
+/// `GET /projects/:id/templates/issues/:name`, from `.gitlab/issue_templates`
+#[derive(Debug, Deserialize, Clone)]
+pub struct GitLabIssueTemplate {
+    pub content: String,
+}
+
+/// A project the token can see, from `GET /projects?membership=true`
+#[derive(Debug, Deserialize, Clone)]
+pub struct GitLabProjectSummary {
+    pub path_with_namespace: String,
+}
@@ -1,16 +1,25 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
+use std::future::Future;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use reqwest::{header::HeaderMap, Client};
 use serde_json::json;
 
 use base64::{engine::general_purpose, Engine as _};
 
-use super::model::{JiraConfig, JiraIssue, JiraProject};
+use super::model::{
+    text_to_adf, JiraComment, JiraComments, JiraConfig, JiraIssue, JiraProject, JiraProjectSearch,
+    JiraProjectSummary, JiraTransition, JiraTransitionField, JiraTransitions, JiraUser,
+};
+use crate::errors::http_error;
+use crate::prompt::{ask_required, choose_one};
 use crate::providers::{common::credentials::HasSecretToken, jira::model::JiraResult};
 use crate::providers::{
-    common::model::{Issue, Label},
-    jira::model::JiraIssueType,
+    common::model::{
+        parse_due_date, parse_timestamp, Assignee, AuthIdentity, Closure, Comment, Issue,
+        IssueDetails, IssueEdit, Label, WorkStatus,
+    },
+    jira::model::{JiraIssueType, JiraStatus},
 };
 use log::debug;
 
@@ -22,88 +31,359 @@ pub fn construct_jira_basic_auth_header(username: &str, token: &str) -> HeaderMa
     headers
 }
 
+/// The issue's page; the `self` of an issue is its REST URL
+fn browse_url(jira_config: &JiraConfig, key: &str) -> String {
+    format!(
+        "{}/browse/{}",
+        jira_config.endpoint.trim_end_matches('/'),
+        key
+    )
+}
+
+/// The configured status of this name, otherwise by the status' category
+fn work_status(project: &JiraProject, status: &JiraStatus) -> Option<WorkStatus> {
+    project
+        .status
+        .status_named(std::iter::once(status.name.as_str()))
+        .or_else(|| match status.status_category.as_ref()?.key.as_str() {
+            "new" => Some(WorkStatus::Todo),
+            "indeterminate" => Some(WorkStatus::Doing),
+            "done" => Some(WorkStatus::Done),
+            _ => None,
+        })
+}
+
+fn to_issue(jira_config: &JiraConfig, project: &JiraProject, jira_issue: JiraIssue) -> Issue {
+    let fields = jira_issue.fields.unwrap_or_default();
+    let status = fields.status.as_ref().and_then(|s| work_status(project, s));
+    Issue {
+        id: format!("{}/{}", project.id, jira_issue.id),
+        store_id: project.id.clone(),
+        provider: "jira".to_string(),
+        title: fields.summary,
+        html_url: browse_url(jira_config, &jira_issue.id),
+        tags: fields
+            .labels
+            .unwrap_or_default()
+            .into_iter()
+            .map(|label| Label { name: label })
+            .collect(),
+        state: fields.status.map(|s| s.name).unwrap_or_default(),
+        assignees: fields
+            .assignee
+            .map(|a| a.display_name)
+            .into_iter()
+            .collect(),
+        author: fields.reporter.map(|r| r.display_name),
+        created_at: fields.created.as_deref().and_then(parse_timestamp),
+        updated_at: fields.updated.as_deref().and_then(parse_timestamp),
+        closed_at: fields.resolutiondate.as_deref().and_then(parse_timestamp),
+        status,
+        due: fields.duedate.as_deref().and_then(parse_due_date),
+        milestone: fields
+            .other
+            .get(&project.sprint_field)
+            .and_then(current_sprint),
+        description: fields.description.map(|d| d.to_text()),
+    }
+}
+
+/// The sprint field is a list of every sprint the issue has been in,
+/// prefer the active one, otherwise the latest
+fn current_sprint(sprints: &serde_json::Value) -> Option<String> {
+    let sprints = sprints.as_array()?;
+    sprints
+        .iter()
+        .find(|s| s["state"] == "active")
+        .or(sprints.last())
+        .and_then(|s| s["name"].as_str())
+        .map(|name| name.to_string())
+}
+
+/// the only fields the issue list needs, keeps the search responses small
+fn list_fields(project: &JiraProject) -> String {
+    format!(
+        "summary,labels,status,assignee,reporter,created,updated,duedate,description,{}",
+        project.sprint_field
+    )
+}
+
 pub async fn collect_tasks_from_jira(
-    jira_config: &Vec<JiraConfig>,
-    issue_store_id: &Option<String>,
+    client: &Client,
+    jira_config: &JiraConfig,
+    project: &JiraProject,
 ) -> Result<Vec<Issue>, anyhow::Error> {
-    let client = Client::new();
-    let mut all_issues = Vec::new();
+    let optional_filter = project
+        .filter
+        .as_ref()
+        .map_or("".to_string(), |filt| format!(" AND {}", filt));
 
-    for j in jira_config {
-        for (_idx, project) in j
-            .projects
-            .iter()
-            .filter(|&r| issue_store_id.is_none() || issue_store_id.as_deref().is_some_and(|p| r.id == p))
-            .enumerate()
-        {
-            let optional_filter = project
-                .filter
-                .as_ref()
-                .map_or("".to_string(), |filt| format!(" AND {}", filt));
+    let jql = format!(
+        "project = \"{}\" AND resolution = unresolved{}",
+        project.project_key, optional_filter
+    );
 
-            // Construct the Jira API URL for fetching issues
-            let url = format!(
-                "{}/rest/api/3/search?jql=project={} AND resolution = unresolved{}&maxResults=1000",
-                j.endpoint, project.project_key, optional_filter
-            );
+    // the enhanced JQL search, which pages with a nextPageToken
+    let url = format!("{}/rest/api/3/search/jql", jira_config.endpoint);
+    let page_size = project.max_issues.clamp(1, 100).to_string();
+    let fields = list_fields(project);
 
-            debug!("{}", url);
-
-            // Send a GET request to fetch issues
-            let response = client
-                .get(&url)
-                .headers(construct_jira_basic_auth_header(
-                    &j.get_username(),
-                    &j.get_token(),
-                ))
-                .send()
-                .await?;
-
-            if response.status().is_success() {
-                let body = response.text().await?;
-
-                debug!("{}", body);
-
-                // Deserialize Jira issues into your internal Issue representation
-                let j_result: JiraResult = serde_json::from_str(&body)?;
-                // Convert Jira issues to the internal Issue representation
-                let issues = j_result.issues.into_iter().map(|jira_issue| Issue {
-                    id: format!("{}/{}", project.id, jira_issue.id),
-                    title: jira_issue.fields.as_ref().unwrap().summary.clone(),
-                    html_url: jira_issue.url,
-                    tags: jira_issue
-                        .fields
-                        .unwrap()
-                        .labels
-                        .unwrap_or_default()
-                        .into_iter()
-                        .map(|label| Label { name: label })
-                        .collect(),
-                });
-
-                all_issues.extend(issues);
-            } else {
-                println!(
-                    "Error: Unable to fetch issues for project_id {}. Status: {:?}",
-                    project.project_key,
-                    response.status()
-                );
-            }
+    // each page's request borrows these
+    let (jql, fields, page_size, url) = (&jql, &fields, &page_size, &url);
+    let (issues, truncated) = collect_pages(project.max_issues, |next_page_token| async move {
+        // reqwest URL encodes the JQL for us
+        let mut query = vec![
+            ("jql", jql.as_str()),
+            ("fields", fields.as_str()),
+            ("maxResults", page_size.as_str()),
+        ];
+        if let Some(token) = next_page_token.as_deref() {
+            query.push(("nextPageToken", token));
         }
+
+        debug!("jira:search {} {:?}", url, query);
+
+        // Send a GET request to fetch issues
+        let response = client
+            .get(url)
+            .query(&query)
+            .headers(construct_jira_basic_auth_header(
+                &jira_config.get_username()?,
+                &jira_config.get_token().await?,
+            ))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(http_error(
+                response,
+                format!("Unable to fetch issues for project {}", project.project_key),
+            )
+            .await);
+        }
+
+        let body = response.text().await?;
+
+        debug!("{}", body);
+
+        Ok(serde_json::from_str::<JiraResult>(&body)?)
+    })
+    .await?;
+
+    if truncated {
+        eprintln!(
+            "Warning: only the first {} issues of {} ({}) are shown, raise max_issues to see more",
+            project.max_issues, project.id, project.project_key
+        );
     }
 
-    Ok(all_issues)
+    // Convert Jira issues to the internal Issue representation
+    Ok(issues
+        .into_iter()
+        .map(|jira_issue| to_issue(jira_config, project, jira_issue))
+        .collect())
+}
+
+/// Fetches search pages with `fetch_page(next_page_token)` until the last one, or until
+/// there are `max_issues`; returns the issues and whether some were left out
+async fn collect_pages<F, Fut>(
+    max_issues: usize,
+    mut fetch_page: F,
+) -> Result<(Vec<JiraIssue>, bool)>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = Result<JiraResult>>,
+{
+    let mut next_page_token: Option<String> = None;
+    let mut issues = Vec::new();
+    let mut truncated = false;
+
+    loop {
+        if issues.len() >= max_issues {
+            truncated = true;
+            break;
+        }
+
+        let j_result = fetch_page(next_page_token.take()).await?;
+        issues.extend(j_result.issues);
+
+        match j_result.next_page_token {
+            Some(token) if !j_result.is_last.unwrap_or(false) => next_page_token = Some(token),
+            _ => break,
+        }
+    }
+
+    if issues.len() > max_issues {
+        truncated = true;
+        issues.truncate(max_issues);
+    }
+
+    Ok((issues, truncated))
+}
+
+/// The most recently resolved issues, newest first (one page, after the configured filter)
+pub async fn collect_closed_tasks_from_jira(
+    client: &Client,
+    jira_config: &JiraConfig,
+    project: &JiraProject,
+    limit: usize,
+) -> Result<Vec<Issue>, anyhow::Error> {
+    let optional_filter = project
+        .filter
+        .as_ref()
+        .map_or("".to_string(), |filt| format!(" AND {}", filt));
+
+    let jql = format!(
+        "project = \"{}\" AND resolution is not EMPTY{} ORDER BY resolutiondate DESC",
+        project.project_key, optional_filter
+    );
+
+    let url = format!("{}/rest/api/3/search/jql", jira_config.endpoint);
+    let fields = format!("{},resolutiondate", list_fields(project));
+    let max_results = limit.clamp(1, 100).to_string();
+
+    debug!("jira:search closed {} {}", url, jql);
+
+    let response = client
+        .get(&url)
+        .query(&[
+            ("jql", jql.as_str()),
+            ("fields", fields.as_str()),
+            ("maxResults", max_results.as_str()),
+        ])
+        .headers(construct_jira_basic_auth_header(
+            &jira_config.get_username()?,
+            &jira_config.get_token().await?,
+        ))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(http_error(
+            response,
+            format!(
+                "Unable to fetch closed issues for project {}",
+                project.project_key
+            ),
+        )
+        .await);
+    }
+
+    let j_result: JiraResult = response.json().await?;
+    Ok(j_result
+        .issues
+        .into_iter()
+        .map(|jira_issue| to_issue(jira_config, project, jira_issue))
+        .collect())
+}
+
+async fn fetch_jira_issue(
+    client: &Client,
+    jira_config: &JiraConfig,
+    issue_key: &str,
+    fields: &str,
+) -> Result<JiraIssue, anyhow::Error> {
+    // v3, so the description and comments come back as ADF documents
+    let url = format!("{}/rest/api/3/issue/{}", jira_config.endpoint, issue_key);
+
+    debug!("jira:get issue {} fields={}", url, fields);
+
+    let response = client
+        .get(&url)
+        .query(&[("fields", fields)])
+        .headers(construct_jira_basic_auth_header(
+            &jira_config.get_username()?,
+            &jira_config.get_token().await?,
+        ))
+        .send()
+        .await?;
+
+    if response.status().is_success() {
+        Ok(response.json::<JiraIssue>().await?)
+    } else {
+        Err(http_error(response, format!("Unable to fetch issue {}", issue_key)).await)
+    }
+}
+
+pub async fn get_issue_jira(
+    client: &Client,
+    jira_config: &JiraConfig,
+    project: &JiraProject,
+    issue_key: &str,
+) -> Result<Issue, anyhow::Error> {
+    let jira_issue =
+        fetch_jira_issue(client, jira_config, issue_key, &list_fields(project)).await?;
+    Ok(to_issue(jira_config, project, jira_issue))
+}
+
+pub async fn get_issue_details_jira(
+    client: &Client,
+    jira_config: &JiraConfig,
+    project: &JiraProject,
+    issue_key: &str,
+) -> Result<IssueDetails, anyhow::Error> {
+    let jira_issue =
+        fetch_jira_issue(client, jira_config, issue_key, &list_fields(project)).await?;
+
+    let url = format!(
+        "{}/rest/api/3/issue/{}/comment",
+        jira_config.endpoint, issue_key
+    );
+    let mut comments: Vec<JiraComment> = Vec::new();
+
+    // a long discussion is more than one page
+    loop {
+        let start_at = comments.len().to_string();
+        debug!("jira:get comments {} startAt={}", url, start_at);
+
+        let response = client
+            .get(&url)
+            .query(&[("startAt", start_at.as_str()), ("maxResults", "100")])
+            .headers(construct_jira_basic_auth_header(
+                &jira_config.get_username()?,
+                &jira_config.get_token().await?,
+            ))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(http_error(
+                response,
+                format!("Unable to fetch the comments of issue {}", issue_key),
+            )
+            .await);
+        }
+
+        let page = response.json::<JiraComments>().await?;
+        let last = page.comments.is_empty() || page.start_at + page.comments.len() >= page.total;
+        comments.extend(page.comments);
+        if last {
+            break;
+        }
+    }
+
+    Ok(IssueDetails {
+        issue: to_issue(jira_config, project, jira_issue),
+        comments: comments
+            .into_iter()
+            .map(|c| Comment {
+                author: c.author.map(|a| a.display_name).unwrap_or_default(),
+                created_at: parse_timestamp(&c.created),
+                body: c.body.map(|b| b.to_text()).unwrap_or_default(),
+            })
+            .collect(),
+    })
 }
 
 pub async fn add_new_task_jira(
+    client: &Client,
     jira_project: &JiraProject,
     jira_config: &JiraConfig,
     title: &str,
     details: &str,
     tags: &Option<Vec<String>>,
-) -> Result<(), anyhow::Error> {
-    let client = Client::new();
-
+    assignee: Option<&Assignee>,
+) -> Result<Issue, anyhow::Error> {
     let add_url = format!("{}/rest/api/2/issue/", jira_config.endpoint);
 
     // TODO we can just use the struct instead of -partial" creation. FIXME
@@ -126,13 +406,19 @@ pub async fn add_new_task_jira(
         );
     }
 
+    if let Some(assignee) = assignee {
+        let scope = ("project", jira_project.project_key.as_str());
+        let user = jira_user(client, jira_config, scope, assignee).await?;
+        issue_details["fields"]["assignee"] = json!({ "accountId": user.account_id });
+    }
+
     debug!("posting {} to {}", issue_details, add_url);
 
     let response = client
         .post(&add_url)
         .headers(construct_jira_basic_auth_header(
-            &jira_config.get_username(),
-            &jira_config.get_token(),
+            &jira_config.get_username()?,
+            &jira_config.get_token().await?,
         ))
         .header(reqwest::header::CONTENT_TYPE, "application/json")
         .json(&issue_details)
@@ -140,26 +426,182 @@ pub async fn add_new_task_jira(
         .await?;
 
     if response.status().is_success() {
+        // the create response only carries the key and links, the summary/labels are what we sent
         let issue: JiraIssue = response.json::<JiraIssue>().await?;
-        println!("New issue created:");
-        println!("ID: {}", issue.id);
-        println!("URL: {}", issue.url);
+        Ok(Issue {
+            id: format!("{}/{}", jira_project.id, issue.id),
+            store_id: jira_project.id.clone(),
+            provider: "jira".to_string(),
+            title: title.to_string(),
+            html_url: browse_url(jira_config, &issue.id),
+            tags: tags
+                .iter()
+                .flatten()
+                .map(|label| Label {
+                    name: label.clone(),
+                })
+                .collect(),
+            description: Some(details.to_string()),
+            ..Default::default()
+        })
     } else {
-        println!(
-            "Error: Unable to create issue. Status: {:?}",
-            response.status()
-        );
+        Err(http_error(response, "Unable to create issue".to_string()).await)
+    }
+}
+
+/// A user who can be assigned issues in the scope (`issueKey` or `project`), found
+/// by e-mail or name; `--me` being the user of the token
+async fn jira_user(
+    client: &Client,
+    jira_config: &JiraConfig,
+    scope: (&str, &str),
+    assignee: &Assignee,
+) -> Result<JiraUser, anyhow::Error> {
+    let request = match assignee {
+        Assignee::Me => client.get(format!("{}/rest/api/3/myself", jira_config.endpoint)),
+        Assignee::User(query) => client
+            .get(format!(
+                "{}/rest/api/3/user/assignable/search",
+                jira_config.endpoint
+            ))
+            .query(&[scope, ("query", query.as_str())]),
+    };
+
+    debug!("jira:get user {}", assignee);
+
+    let response = request
+        .headers(construct_jira_basic_auth_header(
+            &jira_config.get_username()?,
+            &jira_config.get_token().await?,
+        ))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(http_error(response, format!("Unable to find the user {}", assignee)).await);
+    }
+
+    let query = match assignee {
+        Assignee::Me => return Ok(response.json::<JiraUser>().await?),
+        Assignee::User(query) => query,
+    };
+
+    // the search matches the start of names and e-mails, so prefer an exact match
+    let mut users: Vec<JiraUser> = response.json().await?;
+    let exact = users.iter().position(|user| {
+        user.display_name.eq_ignore_ascii_case(query)
+            || user
+                .email_address
+                .as_ref()
+                .is_some_and(|email| email.eq_ignore_ascii_case(query))
+    });
+    match (exact, users.len()) {
+        (Some(i), _) => Ok(users.swap_remove(i)),
+        (None, 1) => Ok(users.remove(0)),
+        (None, 0) => Err(anyhow!(
+            "no user matching '{}' can be assigned issues in {}",
+            query,
+            scope.1
+        )),
+        (None, _) => Err(anyhow!(
+            "'{}' matches {}, use their e-mail",
+            query,
+            users
+                .iter()
+                .map(|user| user.display_name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// `accountId` null unassigns the issue
+async fn set_jira_assignee(
+    client: &Client,
+    jira_config: &JiraConfig,
+    issue_key: &str,
+    account_id: Option<&str>,
+) -> Result<(), anyhow::Error> {
+    let url = format!(
+        "{}/rest/api/3/issue/{}/assignee",
+        jira_config.endpoint, issue_key
+    );
+
+    debug!("jira:assign {} {:?}", url, account_id);
+
+    let response = client
+        .put(&url)
+        .headers(construct_jira_basic_auth_header(
+            &jira_config.get_username()?,
+            &jira_config.get_token().await?,
+        ))
+        .json(&json!({ "accountId": account_id }))
+        .send()
+        .await?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(http_error(
+            response,
+            format!("Unable to change the assignee of {}", issue_key),
+        )
+        .await)
     }
+}
+
+pub async fn assign_jira_issue(
+    client: &Client,
+    jira_config: &JiraConfig,
+    issue_key: &str,
+    assignee: &Assignee,
+) -> Result<(), anyhow::Error> {
+    let user = jira_user(client, jira_config, ("issueKey", issue_key), assignee).await?;
+    set_jira_assignee(client, jira_config, issue_key, user.account_id.as_deref()).await?;
+    println!("Issue {} assigned to {}.", issue_key, user.display_name);
+    Ok(())
+}
+
+pub async fn unassign_jira_issue(
+    client: &Client,
+    jira_config: &JiraConfig,
+    issue_key: &str,
+    assignee: Option<&Assignee>,
+) -> Result<(), anyhow::Error> {
+    let current = fetch_jira_issue(client, jira_config, issue_key, "assignee")
+        .await?
+        .fields
+        .and_then(|fields| fields.assignee);
+    let Some(current) = current else {
+        println!("Issue {} has no assignee.", issue_key);
+        return Ok(());
+    };
+
+    if let Some(assignee) = assignee {
+        let user = jira_user(client, jira_config, ("issueKey", issue_key), assignee).await?;
+        if user.account_id != current.account_id {
+            println!(
+                "Issue {} is assigned to {}, not {}.",
+                issue_key, current.display_name, user.display_name
+            );
+            return Ok(());
+        }
+    }
+
+    set_jira_assignee(client, jira_config, issue_key, None).await?;
+    println!(
+        "Issue {} unassigned from {}.",
+        issue_key, current.display_name
+    );
     Ok(())
 }
 
 pub async fn remove_labels_from_jira_issue(
+    client: &Client,
     jira_config: &JiraConfig,
-    issue_id: &String,
+    issue_id: &str,
     tags: &HashSet<String>,
 ) -> Result<(), anyhow::Error> {
-    let client = Client::new();
-
     // The Jira API endpoint for updating an issue
     let issue_url = format!("{}/rest/api/2/issue/{}", jira_config.endpoint, issue_id);
 
@@ -183,35 +625,32 @@ pub async fn remove_labels_from_jira_issue(
     let response = client
         .put(&issue_url)
         .headers(construct_jira_basic_auth_header(
-            &jira_config.get_username(),
-            &jira_config.get_token(),
+            &jira_config.get_username()?,
+            &jira_config.get_token().await?,
         ))
         .header(reqwest::header::CONTENT_TYPE, "application/json")
         .json(&update)
         .send()
         .await?;
 
-    if response.status().is_success() {
-        println!("Labels {:?} removed from issue {}.", tags, issue_id);
-    } else {
-        println!(
-            "Error: Unable to add labels to issue {}. Status: {:?} Code: {:?}",
-            issue_id,
-            response.status(),
-            response.text().await?,
-        );
+    if !response.status().is_success() {
+        return Err(http_error(
+            response,
+            format!("Unable to remove labels from issue {}", issue_id),
+        )
+        .await);
     }
 
+    println!("Labels {:?} removed from issue {}.", tags, issue_id);
     Ok(())
 }
 
 pub async fn add_labels_to_jira_issue(
+    client: &Client,
     jira_config: &JiraConfig,
-    issue_id: &String,
+    issue_id: &str,
     tags: &HashSet<String>,
 ) -> Result<(), anyhow::Error> {
-    let client = Client::new();
-
     // The Jira API endpoint for updating an issue
     let issue_url = format!("{}/rest/api/2/issue/{}", jira_config.endpoint, issue_id);
 
@@ -235,119 +674,413 @@ pub async fn add_labels_to_jira_issue(
     let response = client
         .put(&issue_url)
         .headers(construct_jira_basic_auth_header(
-            &jira_config.get_username(),
-            &jira_config.get_token(),
+            &jira_config.get_username()?,
+            &jira_config.get_token().await?,
         ))
         .header(reqwest::header::CONTENT_TYPE, "application/json")
         .json(&update)
         .send()
         .await?;
 
-    if response.status().is_success() {
-        println!("Labels {:?} added to issue {}.", tags, issue_id);
-    } else {
-        println!(
-            "Error: Unable to add labels to issue {}. Status: {:?} Code: {:?}",
-            issue_id,
-            response.status(),
-            response.text().await?,
-        );
+    if !response.status().is_success() {
+        return Err(http_error(
+            response,
+            format!("Unable to add labels to issue {}", issue_id),
+        )
+        .await);
     }
 
+    println!("Labels {:?} added to issue {}.", tags, issue_id);
     Ok(())
 }
 
+/// The transition to a status, by name, otherwise the first to a status in the category
+/// (new, indeterminate or done)
+fn find_transition<'a>(
+    transitions: &'a [JiraTransition],
+    status: Option<&str>,
+    category: &str,
+) -> Option<&'a JiraTransition> {
+    match status {
+        Some(status) => transitions
+            .iter()
+            .find(|t| t.to.name.eq_ignore_ascii_case(status)),
+        None => transitions.iter().find(|t| {
+            t.to.status_category
+                .as_ref()
+                .is_some_and(|c| c.key == category)
+        }),
+    }
+}
+
+fn describe_transitions(transitions: &[JiraTransition]) -> String {
+    transitions
+        .iter()
+        .map(|t| format!("{} {} -> {}", t.id, t.name, t.to.name))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Close with the transition to `close_status`, or to a Done status, as the issue's
+/// workflow allows; `close_transition_id` when there is neither
 pub async fn close_issue_jira(
+    client: &Client,
     jira_config: &JiraConfig,
     project_config: &JiraProject,
-    issue_id: &String,
+    issue_id: &str,
+    closure: &Closure,
 ) -> Result<(), anyhow::Error> {
-    let client = Client::new();
+    let transitions = get_jira_transitions(client, jira_config, issue_id).await?;
+    let found = find_transition(&transitions, project_config.close_status.as_deref(), "done");
 
-    // The Jira API endpoint for transitioning issues
-    let transition_url = format!(
-        "{}/rest/api/3/issue/{}/transitions",
-        jira_config.endpoint, issue_id
-    );
+    let transition_id = match (found, &project_config.close_transition_id) {
+        (Some(t), _) => {
+            debug!("closing {} with transition {} {}", issue_id, t.id, t.name);
+            t.id.clone()
+        }
+        (None, Some(id)) => id.clone(),
+        (None, None) => {
+            return Err(anyhow!(
+                "Unable to close issue {}, none of its transitions ({}) are to {}, set close_status or close_transition_id on project {}",
+                issue_id,
+                describe_transitions(&transitions),
+                project_config
+                    .close_status
+                    .as_deref()
+                    .map_or("a Done status".to_string(), |s| format!("'{}'", s)),
+                project_config.project_key
+            ))
+        }
+    };
 
-    // The payload for the transition request
-    let transition_payload = json!({
+    let mut transition_payload = json!({
         "transition": {
-            "id": project_config.close_transition_id,// this is a jira instance, project specific value
-            // use tskmstr jira-transitions <issue-id> to locate it
+            "id": transition_id,
         }
     });
 
+    // the resolution and comment are set by the transition itself,
+    // so the issue is never resolved without them
+    if let Some(reason) = closure.reason {
+        transition_payload["fields"] = json!({
+            "resolution": { "name": project_config.resolutions.name_for(reason) }
+        });
+    }
+    if let Some(comment) = &closure.comment {
+        transition_payload["update"] = json!({
+            "comment": [{ "add": { "body": comment } }]
+        });
+    }
+
+    post_jira_transition(
+        client,
+        jira_config,
+        issue_id,
+        transition_payload,
+        format!(
+            "Unable to close issue {} with transition_id={}",
+            issue_id, transition_id
+        ),
+    )
+    .await?;
+
+    println!("Issue {} has been closed.", issue_id);
+    Ok(())
+}
+
+/// Reopen with `reopen_transition_id`, otherwise the transition back to a
+/// "To Do" (statusCategory new) status
+pub async fn reopen_issue_jira(
+    client: &Client,
+    jira_config: &JiraConfig,
+    project_config: &JiraProject,
+    issue_id: &str,
+) -> Result<(), anyhow::Error> {
+    let transition_id = match &project_config.reopen_transition_id {
+        Some(id) => id.clone(),
+        None => {
+            let transitions = get_jira_transitions(client, jira_config, issue_id).await?;
+            find_transition(&transitions, None, "new")
+                .map(|t| t.id.clone())
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Unable to reopen issue {}, none of its transitions ({}) are to a To Do status, set reopen_transition_id on project {}",
+                        issue_id,
+                        describe_transitions(&transitions),
+                        project_config.project_key
+                    )
+                })?
+        }
+    };
+
+    post_jira_transition(
+        client,
+        jira_config,
+        issue_id,
+        json!({ "transition": { "id": transition_id } }),
+        format!(
+            "Unable to reopen issue {}. Do you have the transition_id correct ? transition_id={}",
+            issue_id, transition_id
+        ),
+    )
+    .await?;
+
+    println!("Issue {} has been reopened.", issue_id);
+    Ok(())
+}
+
+async fn post_jira_transition(
+    client: &Client,
+    jira_config: &JiraConfig,
+    issue_id: &str,
+    transition_payload: serde_json::Value,
+    doing: String,
+) -> Result<(), anyhow::Error> {
+    // The Jira API endpoint for transitioning issues; v2, as comments are plain text
+    // there (as they are in add_comment_to_jira_issue)
+    let transition_url = format!(
+        "{}/rest/api/2/issue/{}/transitions",
+        jira_config.endpoint, issue_id
+    );
+
     debug!("posting {} to {}", transition_payload, transition_url);
 
     let response = client
         .post(&transition_url)
         .headers(construct_jira_basic_auth_header(
-            &jira_config.get_username(),
-            &jira_config.get_token(),
+            &jira_config.get_username()?,
+            &jira_config.get_token().await?,
         ))
         .header(reqwest::header::CONTENT_TYPE, "application/json")
         .json(&transition_payload)
         .send()
         .await?;
 
-    if response.status().is_success() {
-        println!("Issue {} has been closed.", issue_id);
-    } else {
+    if !response.status().is_success() {
+        return Err(http_error(response, doing).await);
+    }
+    Ok(())
+}
+
+/// The transitions of an issue in a table, with the fields each one asks for
+pub fn print_jira_transitions(issue_key: &str, transitions: &[JiraTransition]) {
+    println!("Transitions of {}:", issue_key);
+    println!(
+        "  {:>6}  {:<24} {:<24} {:<14} Asks for",
+        "ID", "Transition", "To status", "Category"
+    );
+    for t in transitions {
+        let category = t.to.status_category.as_ref().map_or("", |c| c.key.as_str());
+        let asks_for = t
+            .required_fields()
+            .iter()
+            .map(|(_, field)| field.name.clone())
+            .collect::<Vec<String>>()
+            .join(", ");
         println!(
-            "Error: Unable to close issue {}. Do you have the transition_id correct ? transition_id={}, Status: {:?}",
-            issue_id,
-            project_config.close_transition_id,
-            response.status()
+            "  {:>6}  {:<24} {:<24} {:<14} {}",
+            t.id, t.name, t.to.name, category, asks_for
         );
     }
+}
+
+/// Ask for a field the transition requires, in the shape Jira wants it
+fn ask_transition_field(key: &str, field: &JiraTransitionField) -> Result<serde_json::Value> {
+    let kind = field.schema.as_ref().map_or("string", |s| s.kind.as_str());
+
+    if !field.allowed_values.is_empty() {
+        let labels: Vec<String> = field.allowed_values.iter().map(|v| v.label()).collect();
+        println!("{} ({}):", field.name, key);
+        let picked = &field.allowed_values[choose_one("Which one", &labels)?];
+        let value = match &picked.id {
+            Some(id) => json!({ "id": id }),
+            None => json!({ "name": picked.label() }),
+        };
+        return Ok(match kind {
+            "array" => json!([value]),
+            _ => value,
+        });
+    }
+
+    let answer = ask_required(&format!("{} ({})", field.name, key), "")?;
+    Ok(match kind {
+        "number" => answer
+            .parse::<f64>()
+            .map(|n| json!(n))
+            .map_err(|_| anyhow!("{} must be a number", field.name))?,
+        "array" => json!(answer
+            .split(',')
+            .map(str::trim)
+            .filter(|a| !a.is_empty())
+            .collect::<Vec<&str>>()),
+        _ => json!(answer),
+    })
+}
+
+/// Move an issue along the transition with this name, or to the status with this name,
+/// asking for any fields the transition requires. Without one, the transitions are listed
+pub async fn move_issue_jira(
+    client: &Client,
+    jira_config: &JiraConfig,
+    issue_key: &str,
+    to: Option<&str>,
+) -> Result<(), anyhow::Error> {
+    let transitions = get_jira_transitions(client, jira_config, issue_key).await?;
+
+    let to = match to {
+        Some(to) => to,
+        None => {
+            print_jira_transitions(issue_key, &transitions);
+            return Ok(());
+        }
+    };
+
+    let transition = transitions
+        .iter()
+        .find(|t| t.name.eq_ignore_ascii_case(to) || t.id == to)
+        .or_else(|| {
+            transitions
+                .iter()
+                .find(|t| t.to.name.eq_ignore_ascii_case(to))
+        });
+    let transition = match transition {
+        Some(t) => t,
+        None => {
+            print_jira_transitions(issue_key, &transitions);
+            return Err(anyhow!(
+                "{} has no transition, or status, named '{}'",
+                issue_key,
+                to
+            ));
+        }
+    };
+
+    take_jira_transition(client, jira_config, issue_key, transition).await
+}
+
+/// Move the issue along the transition, asking for the fields it requires
+async fn take_jira_transition(
+    client: &Client,
+    jira_config: &JiraConfig,
+    issue_key: &str,
+    transition: &JiraTransition,
+) -> Result<(), anyhow::Error> {
+    let mut fields = serde_json::Map::new();
+    for (key, field) in transition.required_fields() {
+        fields.insert(key.clone(), ask_transition_field(key, field)?);
+    }
+
+    let mut transition_payload = json!({ "transition": { "id": transition.id } });
+    if !fields.is_empty() {
+        transition_payload["fields"] = serde_json::Value::Object(fields);
+    }
+
+    post_jira_transition(
+        client,
+        jira_config,
+        issue_key,
+        transition_payload,
+        format!(
+            "Unable to move issue {} with transition {} ({})",
+            issue_key, transition.name, transition.id
+        ),
+    )
+    .await?;
+
+    println!("Issue {} moved to {}.", issue_key, transition.to.name);
     Ok(())
 }
 
-/// a utility method, to determine what Transition Id's to put in config
-pub async fn list_jira_transition_ids(
+/// Move an issue to the project's status for todo, doing or done, otherwise to the
+/// first status in the matching category
+pub async fn set_status_jira(
+    client: &Client,
     jira_config: &JiraConfig,
-    issue_id: &String,
+    project_config: &JiraProject,
+    issue_key: &str,
+    status: WorkStatus,
 ) -> Result<(), anyhow::Error> {
-    let client = Client::new();
+    let name = project_config.status.name_for(status);
 
-    // The Jira API endpoint for transitioning issues
-    let transition_url = format!(
-        "{}/rest/api/3/issue/{}/transitions",
-        jira_config.endpoint, issue_id
-    );
+    // done is closing, with close_status and close_transition_id
+    if status == WorkStatus::Done && name.is_none() {
+        return close_issue_jira(
+            client,
+            jira_config,
+            project_config,
+            issue_key,
+            &Closure::default(),
+        )
+        .await;
+    }
+
+    let category = match status {
+        WorkStatus::Todo => "new",
+        WorkStatus::Doing => "indeterminate",
+        WorkStatus::Done => "done",
+    };
+    let transitions = get_jira_transitions(client, jira_config, issue_key).await?;
+    match find_transition(&transitions, name, category) {
+        Some(transition) => take_jira_transition(client, jira_config, issue_key, transition).await,
+        None => {
+            print_jira_transitions(issue_key, &transitions);
+            Err(anyhow!(
+                "{} has no transition to {}, set status.{} on project {}",
+                issue_key,
+                name.map_or(format!("a {} status", status), |n| format!("'{}'", n)),
+                status,
+                project_config.project_key
+            ))
+        }
+    }
+}
+
+/// v3, so the description is sent as an ADF document
+pub async fn edit_jira_issue(
+    client: &Client,
+    jira_config: &JiraConfig,
+    issue_key: &str,
+    edit: &IssueEdit,
+) -> Result<(), anyhow::Error> {
+    let url = format!("{}/rest/api/3/issue/{}", jira_config.endpoint, issue_key);
+
+    let mut fields = json!({});
+    if let Some(title) = &edit.title {
+        fields["summary"] = json!(title);
+    }
+    if let Some(body) = &edit.body {
+        fields["description"] = match body.trim() {
+            "" => serde_json::Value::Null,
+            body => text_to_adf(body),
+        };
+    }
+
+    debug!("jira:put {} {}", url, fields);
 
     let response = client
-        .get(&transition_url)
+        .put(&url)
         .headers(construct_jira_basic_auth_header(
-            &jira_config.get_username(),
-            &jira_config.get_token(),
+            &jira_config.get_username()?,
+            &jira_config.get_token().await?,
         ))
-        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .json(&json!({ "fields": fields }))
         .send()
         .await?;
 
-    if response.status().is_success() {
-        let text = response.text().await?;
-        let json_resp: HashMap<String, serde_json::Value> = serde_json::from_str(&text).unwrap();
-        println!("{}", serde_json::to_string_pretty(&json_resp).unwrap());
-    } else {
-        println!(
-            "Error: Getting the transitions for {}. Status: {:?}",
-            issue_id,
-            response.status()
-        );
+    if !response.status().is_success() {
+        return Err(http_error(response, format!("Unable to edit issue {}", issue_key)).await);
     }
+
+    println!("Issue {} {} updated.", issue_key, edit.describe());
     Ok(())
 }
 
 pub async fn add_comment_to_jira_issue(
+    client: &Client,
     jira_config: &JiraConfig,
     issue_key: &str,
     comment: &str,
-) -> Result<(), reqwest::Error> {
-    let client = reqwest::Client::new();
-
+) -> Result<(), anyhow::Error> {
     // Construct the Jira API URL for creating a new comment
     let url = format!(
         "{}/rest/api/2/issue/{}/comment",
@@ -360,16 +1093,245 @@ pub async fn add_comment_to_jira_issue(
 
     let response = client
         .post(&url)
-        .headers(construct_jira_basic_auth_header(&jira_config.get_username(), &jira_config.get_token()))
+        .headers(construct_jira_basic_auth_header(
+            &jira_config.get_username()?,
+            &jira_config.get_token().await?,
+        ))
         .json(&comment_json)
         .send()
         .await?;
 
-    if response.status().is_success() {
-        println!("Comment added successfully.");
-    } else {
-        eprintln!("Error: Unable to add a comment to the issue. Status: {:?}", response.status());
+    if !response.status().is_success() {
+        return Err(http_error(
+            response,
+            format!("Unable to add a comment to issue {}", issue_key),
+        )
+        .await);
     }
 
+    println!("Comment added successfully.");
     Ok(())
 }
+
+/// `GET /myself`, Jira API tokens have no scopes or expiry to report
+pub async fn whoami_jira(
+    client: &Client,
+    jira_config: &JiraConfig,
+) -> Result<AuthIdentity, anyhow::Error> {
+    let url = format!("{}/rest/api/3/myself", jira_config.endpoint);
+
+    debug!("jira:get myself {}", url);
+
+    let response = client
+        .get(&url)
+        .headers(construct_jira_basic_auth_header(
+            &jira_config.get_username()?,
+            &jira_config.get_token().await?,
+        ))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(http_error(
+            response,
+            "Unable to read the authenticated user".to_string(),
+        )
+        .await);
+    }
+
+    let user: JiraUser = response.json().await?;
+    Ok(AuthIdentity {
+        user: match user.email_address {
+            Some(email) => format!("{} <{}>", user.display_name, email),
+            None => user.display_name,
+        },
+        scopes: None,
+        expires: None,
+    })
+}
+
+/// The projects the user can browse
+pub async fn list_accessible_projects_jira(
+    client: &Client,
+    jira_config: &JiraConfig,
+) -> Result<Vec<JiraProjectSummary>, anyhow::Error> {
+    let url = format!(
+        "{}/rest/api/3/project/search?maxResults=100",
+        jira_config.endpoint
+    );
+
+    debug!("jira:get projects {}", url);
+
+    let response = client
+        .get(&url)
+        .headers(construct_jira_basic_auth_header(
+            &jira_config.get_username()?,
+            &jira_config.get_token().await?,
+        ))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(http_error(response, "Unable to list the projects".to_string()).await);
+    }
+
+    let search: JiraProjectSearch = response.json().await?;
+    Ok(search.values)
+}
+
+/// The transitions the workflow allows for an issue, in its current status
+pub async fn get_jira_transitions(
+    client: &Client,
+    jira_config: &JiraConfig,
+    issue_key: &str,
+) -> Result<Vec<JiraTransition>, anyhow::Error> {
+    let url = format!(
+        "{}/rest/api/3/issue/{}/transitions",
+        jira_config.endpoint, issue_key
+    );
+
+    debug!("jira:get transitions {}", url);
+
+    let response = client
+        .get(&url)
+        .query(&[("expand", "transitions.fields")])
+        .headers(construct_jira_basic_auth_header(
+            &jira_config.get_username()?,
+            &jira_config.get_token().await?,
+        ))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(http_error(
+            response,
+            format!("Unable to get the transitions for {}", issue_key),
+        )
+        .await);
+    }
+
+    let transitions: JiraTransitions = response.json().await?;
+    Ok(transitions.transitions)
+}
+
+/// The key of any open issue in the project, e.g. to read the workflow's transitions from
+pub async fn find_open_issue_key_jira(
+    client: &Client,
+    jira_config: &JiraConfig,
+    project_key: &str,
+) -> Result<Option<String>, anyhow::Error> {
+    let url = format!("{}/rest/api/3/search/jql", jira_config.endpoint);
+    let jql = format!("project = \"{}\" AND resolution = unresolved", project_key);
+
+    let response = client
+        .get(&url)
+        .query(&[
+            ("jql", jql.as_str()),
+            ("fields", "summary"),
+            ("maxResults", "1"),
+        ])
+        .headers(construct_jira_basic_auth_header(
+            &jira_config.get_username()?,
+            &jira_config.get_token().await?,
+        ))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(http_error(
+            response,
+            format!("Unable to search the issues of {}", project_key),
+        )
+        .await);
+    }
+
+    let result: JiraResult = response.json().await?;
+    Ok(result.issues.into_iter().next().map(|i| i.id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// a search page with issues K-`first`.. K-`last`
+    fn page(first: usize, last: usize, next_page_token: Option<&str>, is_last: bool) -> JiraResult {
+        let issues: Vec<_> = (first..=last)
+            .map(|n| {
+                json!({
+                    "key": format!("K-{}", n),
+                    "self": format!("https://example.atlassian.net/rest/api/3/issue/{}", n),
+                })
+            })
+            .collect();
+        serde_json::from_value(json!({
+            "issues": issues,
+            "nextPageToken": next_page_token,
+            "isLast": is_last,
+        }))
+        .unwrap()
+    }
+
+    fn keys(issues: &[JiraIssue]) -> Vec<&str> {
+        issues.iter().map(|issue| issue.id.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn pages_are_followed_to_the_last() {
+        let asked = RefCell::new(Vec::new());
+        let (issues, truncated) = collect_pages(10, |token| {
+            asked.borrow_mut().push(token.clone());
+            let page = match token.as_deref() {
+                None => page(1, 2, Some("p2"), false),
+                Some("p2") => page(3, 4, Some("p3"), false),
+                _ => page(5, 5, None, true),
+            };
+            async move { Ok(page) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(keys(&issues), ["K-1", "K-2", "K-3", "K-4", "K-5"]);
+        assert!(!truncated);
+        assert_eq!(
+            *asked.borrow(),
+            [None, Some("p2".to_string()), Some("p3".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn is_last_stops_even_with_a_token() {
+        let (issues, truncated) = collect_pages(10, |_| async { Ok(page(1, 2, Some("p2"), true)) })
+            .await
+            .unwrap();
+
+        assert_eq!(keys(&issues), ["K-1", "K-2"]);
+        assert!(!truncated);
+    }
+
+    #[tokio::test]
+    async fn max_issues_stops_the_paging() {
+        let pages = RefCell::new(0);
+        let (issues, truncated) = collect_pages(3, |_| {
+            *pages.borrow_mut() += 1;
+            let first = *pages.borrow() * 2 - 1;
+            async move { Ok(page(first, first + 1, Some("more"), false)) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(keys(&issues), ["K-1", "K-2", "K-3"]);
+        assert!(truncated);
+        assert_eq!(*pages.borrow(), 2);
+    }
+
+    #[tokio::test]
+    async fn a_full_last_page_is_not_truncated() {
+        let (issues, truncated) = collect_pages(2, |_| async { Ok(page(1, 2, None, true)) })
+            .await
+            .unwrap();
+
+        assert_eq!(keys(&issues), ["K-1", "K-2"]);
+        assert!(!truncated);
+    }
+}
@@ -1,34 +1,53 @@
-use colored::Color;
 use serde::{Deserialize, Serialize};
 use serde_inline_default::serde_inline_default;
-use std::str::FromStr;
+use serde_json::json;
+use std::collections::HashMap;
 
 use crate::{
-    config::{Defaults, IssueTaskRepository},
-    providers::common::credentials::{CredentialKeyringEntry, HasSecretToken},
+    config::{Defaults, IssueTaskRepository, IssueTemplate, StatusMapping},
+    providers::common::{
+        credentials::{Credential, HasSecretToken},
+        model::CloseReason,
+    },
 };
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct JiraResult {
     pub issues: Vec<JiraIssue>,
+
+    /// present when there is another page of results
+    #[serde(rename = "nextPageToken")]
+    pub next_page_token: Option<String>,
+
+    #[serde(rename = "isLast")]
+    pub is_last: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct JiraIssue {
     #[serde(rename(deserialize = "key"))]
     pub id: String,
-
-    #[serde(rename(deserialize = "self"))]
-    pub url: String,
     pub fields: Option<JiraFields>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Default)]
 pub struct JiraFields {
+    /// only there when asked for, `fields=assignee` leaves it out
+    #[serde(default)]
     pub summary: String,
     pub description: Option<JiraDescription>,
     pub labels: Option<Vec<String>>,
-    pub issuetype: Option<JiraIssueType>,
+    pub status: Option<JiraStatus>,
+    pub assignee: Option<JiraUser>,
+    pub reporter: Option<JiraUser>,
+    pub created: Option<String>,
+    pub updated: Option<String>,
+    pub resolutiondate: Option<String>,
+    pub duedate: Option<String>,
+
+    /// custom fields, such as the sprint
+    #[serde(flatten)]
+    pub other: HashMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -36,25 +55,133 @@ pub struct JiraIssueType {
     pub name: String,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct JiraStatus {
+    pub name: String,
+
+    #[serde(rename = "statusCategory")]
+    pub status_category: Option<JiraStatusCategory>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct JiraUser {
+    #[serde(rename = "accountId", default)]
+    pub account_id: Option<String>,
+
+    #[serde(rename = "displayName")]
+    pub display_name: String,
+
+    #[serde(rename = "emailAddress", default)]
+    pub email_address: Option<String>,
+}
+
+/// A page of an issue's comments
+#[derive(Debug, Deserialize, Clone)]
+pub struct JiraComments {
+    pub comments: Vec<JiraComment>,
+
+    #[serde(rename = "startAt", default)]
+    pub start_at: usize,
+
+    #[serde(default)]
+    pub total: usize,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct JiraComment {
+    pub author: Option<JiraUser>,
+    pub body: Option<JiraDescription>,
+    pub created: String,
+}
+
+/// A description (or comment body) in the Atlassian Document Format, as returned by the v3 API
 #[derive(Debug, Deserialize, Clone)]
 pub struct JiraDescription {
     pub content: Vec<JiraDescriptionContent>,
 }
 
+/// A block of the document; a paragraph, heading, list, code block ..
 #[derive(Debug, Deserialize, Clone)]
 pub struct JiraDescriptionContent {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
     pub content: Vec<JiraDescriptionContentItem>,
 }
 
+/// Text, or a node (list item, hard break, nested paragraph) holding more items
 #[derive(Debug, Deserialize, Clone)]
 pub struct JiraDescriptionContentItem {
-    pub text: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub text: Option<String>,
+    #[serde(default)]
+    pub content: Vec<JiraDescriptionContentItem>,
+}
+
+impl JiraDescription {
+    /// Flatten the document into plain text, one block per paragraph
+    pub fn to_text(&self) -> String {
+        self.content
+            .iter()
+            .map(|block| block.to_text())
+            .collect::<Vec<String>>()
+            .join("\n\n")
+    }
+}
+
+/// Plain text as an ADF document for the v3 API; a paragraph per blank-line separated
+/// block, with hard breaks for its line breaks
+pub fn text_to_adf(text: &str) -> serde_json::Value {
+    let paragraphs: Vec<serde_json::Value> = text
+        .split("\n\n")
+        .map(|block| block.trim_matches('\n'))
+        .filter(|block| !block.trim().is_empty())
+        .map(|block| {
+            let mut content = Vec::new();
+            for (i, line) in block.lines().enumerate() {
+                if i > 0 {
+                    content.push(json!({ "type": "hardBreak" }));
+                }
+                if !line.is_empty() {
+                    content.push(json!({ "type": "text", "text": line }));
+                }
+            }
+            json!({ "type": "paragraph", "content": content })
+        })
+        .collect();
+
+    json!({ "type": "doc", "version": 1, "content": paragraphs })
+}
+
+impl JiraDescriptionContent {
+    fn to_text(&self) -> String {
+        match self.kind.as_str() {
+            "bulletList" | "orderedList" => self
+                .content
+                .iter()
+                .map(|item| format!("- {}", item.to_text()))
+                .collect::<Vec<String>>()
+                .join("\n"),
+            _ => self.content.iter().map(|item| item.to_text()).collect(),
+        }
+    }
+}
+
+impl JiraDescriptionContentItem {
+    fn to_text(&self) -> String {
+        match (self.kind.as_str(), &self.text) {
+            (_, Some(text)) => text.clone(),
+            ("hardBreak", None) => "\n".to_string(),
+            _ => self.content.iter().map(|item| item.to_text()).collect(),
+        }
+    }
 }
 
 #[serde_inline_default]
 #[derive(Debug, Deserialize, Clone)]
 pub struct JiraConfig {
-    pub credential: Option<CredentialKeyringEntry>,
+    pub credential: Option<Credential>,
 
     // Required Value with your Jira endpoint URL
     pub endpoint: String,
@@ -71,7 +198,7 @@ impl HasSecretToken for JiraConfig {
         self.provider_id.clone()
     }
 
-    fn credential(&self) -> Option<CredentialKeyringEntry> {
+    fn credential(&self) -> Option<Credential> {
         self.credential.clone()
     }
 }
@@ -94,26 +221,257 @@ pub struct JiraProject {
     #[serde_inline_default("Task".to_string())]
     pub default_issue_type: String,
 
-    #[serde_inline_default("1".to_string())]
-    pub close_transition_id: String,
+    /// The status `close` moves issues to, when it isn't the first of the
+    /// workflow's Done (statusCategory done) statuses, e.g. "Closed"
+    pub close_status: Option<String>,
+
+    /// The transition `close` uses when the workflow has none to `close_status`,
+    /// or to a Done status (see `tskmstr jira-transitions <ISSUE-KEY>`)
+    pub close_transition_id: Option<String>,
+
+    /// The transition `reopen` uses; without it, the one to a "To Do" status is found
+    pub reopen_transition_id: Option<String>,
+
+    /// The resolution set for each `close --reason`
+    #[serde_inline_default(JiraResolutions::default())]
+    pub resolutions: JiraResolutions,
+
+    /// The custom field holding the sprint, which differs between Jira instances
+    #[serde_inline_default("customfield_10020".to_string())]
+    pub sprint_field: String,
 
     /// Defauls configuration
     pub defaults: Option<Defaults>,
 
     /// filter certain issues
     pub filter: Option<String>,
+
+    /// Stop paging through the issues once this many have been read
+    #[serde_inline_default(500)]
+    pub max_issues: usize,
+
+    /// The status names of todo, doing and done, otherwise the status categories
+    /// (To Do, In Progress, Done) are used
+    #[serde(default)]
+    pub status: StatusMapping,
+
+    /// What a new issue starts from in $EDITOR
+    pub template: Option<IssueTemplate>,
+}
+
+/// Resolution names, which differ between Jira instances (and languages)
+#[serde_inline_default]
+#[derive(Debug, Deserialize, Clone)]
+pub struct JiraResolutions {
+    #[serde_inline_default("Done".to_string())]
+    pub completed: String,
+
+    #[serde_inline_default("Won't Do".to_string())]
+    pub not_planned: String,
+
+    #[serde_inline_default("Duplicate".to_string())]
+    pub duplicate: String,
+}
+
+impl Default for JiraResolutions {
+    fn default() -> JiraResolutions {
+        JiraResolutions {
+            completed: "Done".to_string(),
+            not_planned: "Won't Do".to_string(),
+            duplicate: "Duplicate".to_string(),
+        }
+    }
+}
+
+impl JiraResolutions {
+    pub fn name_for(&self, reason: CloseReason) -> &str {
+        match reason {
+            CloseReason::Completed => &self.completed,
+            CloseReason::NotPlanned => &self.not_planned,
+            CloseReason::Duplicate => &self.duplicate,
+        }
+    }
 }
 
 impl IssueTaskRepository for JiraProject {
-    fn defaults(&self) -> Option<Defaults> {
-        self.defaults.clone()
+    fn template(&self) -> Option<&IssueTemplate> {
+        self.template.as_ref()
     }
 
-    fn color(&self) -> Color {
-        Color::from_str(&self.color).unwrap()
+    fn defaults(&self) -> Option<Defaults> {
+        self.defaults.clone()
     }
 
     fn id(&self) -> String {
         self.id.clone()
     }
 }
+
+/// `GET /project/search`
+#[derive(Debug, Deserialize, Clone)]
+pub struct JiraProjectSearch {
+    pub values: Vec<JiraProjectSummary>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct JiraProjectSummary {
+    pub key: String,
+    pub name: String,
+}
+
+/// `GET /issue/{key}/transitions`
+#[derive(Debug, Deserialize, Clone)]
+pub struct JiraTransitions {
+    pub transitions: Vec<JiraTransition>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct JiraTransition {
+    pub id: String,
+    pub name: String,
+    pub to: JiraTransitionTarget,
+
+    /// the fields of the transition's screen, with `expand=transitions.fields`
+    #[serde(default)]
+    pub fields: HashMap<String, JiraTransitionField>,
+}
+
+impl JiraTransition {
+    /// The fields that must be given, as Jira has no default for them
+    pub fn required_fields(&self) -> Vec<(&String, &JiraTransitionField)> {
+        let mut required: Vec<_> = self
+            .fields
+            .iter()
+            .filter(|(_, field)| field.required && !field.has_default_value)
+            .collect();
+        required.sort_by_key(|(key, _)| key.as_str());
+        required
+    }
+}
+
+/// A field on a transition's screen
+#[derive(Debug, Deserialize, Clone)]
+pub struct JiraTransitionField {
+    pub name: String,
+
+    #[serde(default)]
+    pub required: bool,
+
+    #[serde(rename = "hasDefaultValue", default)]
+    pub has_default_value: bool,
+
+    pub schema: Option<JiraFieldSchema>,
+
+    /// the options of a select (or resolution, priority ..) field
+    #[serde(rename = "allowedValues", default)]
+    pub allowed_values: Vec<JiraAllowedValue>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct JiraFieldSchema {
+    /// string, number, array, option, resolution, user ..
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+/// One of the options of a field, named by `name` or `value` depending on the field
+#[derive(Debug, Deserialize, Clone)]
+pub struct JiraAllowedValue {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub value: Option<String>,
+}
+
+impl JiraAllowedValue {
+    pub fn label(&self) -> String {
+        self.name
+            .clone()
+            .or_else(|| self.value.clone())
+            .or_else(|| self.id.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// The status a transition moves an issue to
+#[derive(Debug, Deserialize, Clone)]
+pub struct JiraTransitionTarget {
+    pub name: String,
+
+    #[serde(rename = "statusCategory")]
+    pub status_category: Option<JiraStatusCategory>,
+}
+
+/// new, indeterminate or done
+#[derive(Debug, Deserialize, Clone)]
+pub struct JiraStatusCategory {
+    pub key: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn an_issue_with_only_the_fields_asked_for() {
+        let issue: JiraIssue = serde_json::from_value(json!({
+            "key": "K-1",
+            "self": "https://example.atlassian.net/rest/api/3/issue/10001",
+            "fields": { "assignee": { "accountId": "acc-1", "displayName": "Ann" } }
+        }))
+        .unwrap();
+
+        let fields = issue.fields.unwrap();
+        assert_eq!(fields.summary, "");
+        assert_eq!(
+            fields.assignee.unwrap().account_id.as_deref(),
+            Some("acc-1")
+        );
+    }
+
+    fn description(adf: serde_json::Value) -> JiraDescription {
+        serde_json::from_value(adf).unwrap()
+    }
+
+    #[test]
+    fn text_to_paragraphs_and_hard_breaks() {
+        assert_eq!(
+            text_to_adf("first line\nsecond line\n\n\nnext paragraph"),
+            json!({ "type": "doc", "version": 1, "content": [
+                { "type": "paragraph", "content": [
+                    { "type": "text", "text": "first line" },
+                    { "type": "hardBreak" },
+                    { "type": "text", "text": "second line" },
+                ]},
+                { "type": "paragraph", "content": [
+                    { "type": "text", "text": "next paragraph" },
+                ]},
+            ]})
+        );
+        assert_eq!(
+            text_to_adf("  \n\n"),
+            json!({ "type": "doc", "version": 1, "content": [] })
+        );
+    }
+
+    #[test]
+    fn text_survives_the_round_trip() {
+        let text = "A title line\nand its second line\n\nAnother paragraph";
+        assert_eq!(description(text_to_adf(text)).to_text(), text);
+    }
+
+    #[test]
+    fn lists_and_nested_nodes_to_text() {
+        let adf = json!({ "type": "doc", "version": 1, "content": [
+            { "type": "heading", "content": [{ "type": "text", "text": "Steps" }] },
+            { "type": "bulletList", "content": [
+                { "type": "listItem", "content": [
+                    { "type": "paragraph", "content": [{ "type": "text", "text": "one" }] },
+                ]},
+                { "type": "listItem", "content": [
+                    { "type": "paragraph", "content": [{ "type": "text", "text": "two" }] },
+                ]},
+            ]},
+        ]});
+        assert_eq!(description(adf).to_text(), "Steps\n\n- one\n- two");
+    }
+}
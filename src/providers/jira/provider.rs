@@ -0,0 +1,128 @@
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use reqwest::Client;
+
+use crate::config::IssueTaskRepository;
+use crate::providers::common::{
+    model::{Assignee, Closure, Issue, IssueDetails, IssueEdit, WorkStatus},
+    provider::IssueProvider,
+};
+
+use super::methods::{
+    add_comment_to_jira_issue, add_labels_to_jira_issue, add_new_task_jira, assign_jira_issue,
+    close_issue_jira, collect_closed_tasks_from_jira, collect_tasks_from_jira, edit_jira_issue,
+    get_issue_details_jira, get_issue_jira, move_issue_jira, remove_labels_from_jira_issue,
+    reopen_issue_jira, set_status_jira, unassign_jira_issue,
+};
+use super::model::{JiraConfig, JiraProject};
+
+/// A Jira project, and the Jira instance configuration it was declared under
+pub struct JiraIssueStore {
+    /// shared across every store, so all requests use one connection pool
+    pub client: Client,
+    pub config: JiraConfig,
+    pub project: JiraProject,
+}
+
+#[async_trait]
+impl IssueProvider for JiraIssueStore {
+    fn repository(&self) -> &dyn IssueTaskRepository {
+        &self.project
+    }
+
+    fn provider(&self) -> &'static str {
+        "jira"
+    }
+
+    fn location(&self) -> String {
+        format!("{}/{}", self.config.endpoint, self.project.project_key)
+    }
+
+    async fn list_issues(&self) -> Result<Vec<Issue>, anyhow::Error> {
+        collect_tasks_from_jira(&self.client, &self.config, &self.project).await
+    }
+
+    async fn get_issue(&self, issue_id: &str) -> Result<Issue, anyhow::Error> {
+        get_issue_jira(&self.client, &self.config, &self.project, issue_id).await
+    }
+
+    async fn get_issue_details(&self, issue_id: &str) -> Result<IssueDetails, anyhow::Error> {
+        get_issue_details_jira(&self.client, &self.config, &self.project, issue_id).await
+    }
+
+    async fn create_issue(
+        &self,
+        title: &str,
+        details: &str,
+        tags: &Option<Vec<String>>,
+        assignee: Option<&Assignee>,
+    ) -> Result<Issue, anyhow::Error> {
+        add_new_task_jira(
+            &self.client,
+            &self.project,
+            &self.config,
+            title,
+            details,
+            tags,
+            assignee,
+        )
+        .await
+    }
+
+    async fn close_issue(&self, issue_id: &str, closure: &Closure) -> Result<(), anyhow::Error> {
+        close_issue_jira(&self.client, &self.config, &self.project, issue_id, closure).await
+    }
+
+    async fn reopen_issue(&self, issue_id: &str) -> Result<(), anyhow::Error> {
+        reopen_issue_jira(&self.client, &self.config, &self.project, issue_id).await
+    }
+
+    async fn move_issue(&self, issue_id: &str, to: Option<&str>) -> Result<(), anyhow::Error> {
+        move_issue_jira(&self.client, &self.config, issue_id, to).await
+    }
+
+    async fn set_status(&self, issue_id: &str, status: WorkStatus) -> Result<(), anyhow::Error> {
+        set_status_jira(&self.client, &self.config, &self.project, issue_id, status).await
+    }
+
+    async fn list_closed_issues(&self, limit: usize) -> Result<Vec<Issue>, anyhow::Error> {
+        collect_closed_tasks_from_jira(&self.client, &self.config, &self.project, limit).await
+    }
+
+    async fn edit_issue(&self, issue_id: &str, edit: &IssueEdit) -> Result<(), anyhow::Error> {
+        edit_jira_issue(&self.client, &self.config, issue_id, edit).await
+    }
+
+    async fn assign_issue(&self, issue_id: &str, assignee: &Assignee) -> Result<(), anyhow::Error> {
+        assign_jira_issue(&self.client, &self.config, issue_id, assignee).await
+    }
+
+    async fn unassign_issue(
+        &self,
+        issue_id: &str,
+        assignee: Option<&Assignee>,
+    ) -> Result<(), anyhow::Error> {
+        unassign_jira_issue(&self.client, &self.config, issue_id, assignee).await
+    }
+
+    async fn add_comment(&self, issue_id: &str, comment: &str) -> Result<(), anyhow::Error> {
+        Ok(add_comment_to_jira_issue(&self.client, &self.config, issue_id, comment).await?)
+    }
+
+    async fn add_labels(
+        &self,
+        issue_id: &str,
+        labels: &HashSet<String>,
+    ) -> Result<(), anyhow::Error> {
+        add_labels_to_jira_issue(&self.client, &self.config, issue_id, labels).await
+    }
+
+    async fn remove_labels(
+        &self,
+        issue_id: &str,
+        labels: &HashSet<String>,
+    ) -> Result<(), anyhow::Error> {
+        remove_labels_from_jira_issue(&self.client, &self.config, issue_id, labels).await
+    }
+}
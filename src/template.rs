@@ -0,0 +1,120 @@
+use anyhow::{Context, Result};
+use log::debug;
+use serde::Deserialize;
+use serde_yaml::Value;
+
+use crate::providers::common::provider::IssueProvider;
+
+/// What a new issue starts from in $EDITOR
+#[derive(Debug, Clone, Default)]
+pub struct Template {
+    pub title: String,
+    pub labels: Vec<String>,
+    pub body: String,
+}
+
+/// The YAML GitHub issue templates start with, between `---` lines
+#[derive(Debug, Deserialize, Default)]
+struct FrontMatter {
+    title: Option<String>,
+
+    /// `bug, triage` or a list
+    labels: Option<Value>,
+}
+
+/// Read a template, and the title and labels of its front matter when it has one
+pub fn parse_template(text: &str) -> Template {
+    let text = text.replace("\r\n", "\n");
+
+    let (front, body) = match text
+        .strip_prefix("---\n")
+        .and_then(|t| t.split_once("\n---"))
+    {
+        Some((yaml, rest)) => match serde_yaml::from_str::<FrontMatter>(yaml) {
+            Ok(front) => (front, rest.split_once('\n').map_or("", |(_, body)| body)),
+            Err(e) => {
+                debug!("the template's front matter is not YAML, it is kept: {}", e);
+                (FrontMatter::default(), text.as_str())
+            }
+        },
+        None => (FrontMatter::default(), text.as_str()),
+    };
+
+    let labels = match front.labels {
+        Some(Value::String(labels)) => labels
+            .split(',')
+            .map(|label| label.trim().to_string())
+            .filter(|label| !label.is_empty())
+            .collect(),
+        Some(Value::Sequence(labels)) => labels
+            .into_iter()
+            .filter_map(|label| label.as_str().map(|l| l.to_string()))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    Template {
+        title: front.title.unwrap_or_default(),
+        labels,
+        body: body.trim().to_string(),
+    }
+}
+
+/// The store's `template:`, read from the config, a file or the repository;
+/// an empty one when it has none
+pub async fn issue_template(store: &dyn IssueProvider) -> Result<Template> {
+    let Some(template) = store.repository().template() else {
+        return Ok(Template::default());
+    };
+
+    let text = match (&template.text, &template.file, &template.repo) {
+        (Some(text), _, _) => text.clone(),
+        (None, Some(file), _) => {
+            let path = shellexpand::tilde(file).to_string();
+            std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read the issue template {}", path))?
+        }
+        (None, None, Some(name)) => store.read_repo_template(name).await?,
+        (None, None, None) => return Ok(Template::default()),
+    };
+
+    Ok(parse_template(&text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn front_matter_title_and_labels() {
+        let template = parse_template(
+            "---\nname: Bug\nabout: a bug\ntitle: '[bug] '\nlabels: bug, triage\n---\n\n## Steps\n\n1.\n",
+        );
+        assert_eq!(template.title, "[bug] ");
+        assert_eq!(template.labels, vec!["bug", "triage"]);
+        assert_eq!(template.body, "## Steps\n\n1.");
+    }
+
+    #[test]
+    fn labels_as_a_list() {
+        let template = parse_template("---\r\nlabels: [bug, ui]\r\n---\r\nBody\r\n");
+        assert_eq!(template.labels, vec!["bug", "ui"]);
+        assert_eq!(template.body, "Body");
+    }
+
+    #[test]
+    fn without_front_matter() {
+        let template = parse_template("## Summary\n\n---\n\n## Steps\n");
+        assert_eq!(template.title, "");
+        assert!(template.labels.is_empty());
+        assert_eq!(template.body, "## Summary\n\n---\n\n## Steps");
+    }
+
+    #[test]
+    fn front_matter_that_is_not_yaml_is_kept() {
+        let text = "---\ntitle: [unclosed\n---\nBody";
+        let template = parse_template(text);
+        assert_eq!(template.title, "");
+        assert_eq!(template.body, text);
+    }
+}